@@ -0,0 +1,115 @@
+// Run one search on several workers, each owning the states whose text
+// hashes to its partition. This host relays the state batches between the
+// workers, decides when the search is over and has the engine merge the
+// partitions' results.
+
+interface PartitionedSearchOptions {
+  start: string;
+  target: string;
+  hints: any[];
+  maxDepth: number;
+  // Bidirectional search cannot be partitioned
+  mode?: 'heuristic' | 'optimal';
+  occurrences?: 'all' | 'single';
+  constraints?: Record<string, unknown>;
+  config?: Record<string, unknown>;
+  workers?: number;
+}
+
+// Latest 'status' of a partition
+interface PartitionStatus {
+  idle: boolean;
+  imported: number;
+  frontierBound?: number;
+}
+
+function startWorker(): Promise<Worker> {
+  const worker = new Worker(new URL('./pathfinder-worker-engine.ts', import.meta.url), { type: 'module' });
+  return new Promise(resolve => {
+    worker.onmessage = event => {
+      if (event.data.type === 'initialized') resolve(worker);
+    };
+    worker.postMessage({ type: 'init' });
+  });
+}
+
+export async function runPartitionedSearch(
+  options: PartitionedSearchOptions,
+  onProgress?: (batchesRelayed: number) => void
+): Promise<any> {
+  const count = options.workers ?? Math.max(2, Math.min(navigator.hardwareConcurrency || 2, 8));
+  const mode = options.mode ?? 'heuristic';
+  const workers = await Promise.all(Array.from({ length: count }, startWorker));
+  
+  // Batches relayed to each partition, to tell when none are in flight
+  const relayed = new Array(count).fill(0);
+  const statuses: (PartitionStatus | null)[] = new Array(count).fill(null);
+  let bestCost: number | null = null;
+  
+  // Over once a greedy search finds a path, once no partition can beat an
+  // optimal one, or once every partition is idle with nothing in flight
+  const settled = () => {
+    const quiet = statuses.every((status, i) => status !== null && status.imported === relayed[i]);
+    if (bestCost !== null) {
+      return mode === 'heuristic' || (quiet && statuses.every(status => status!.frontierBound === undefined || status!.frontierBound >= bestCost!));
+    }
+    return quiet && statuses.every(status => status!.idle);
+  };
+  
+  const finalResults = await new Promise<any[]>(resolve => {
+    const results: any[] = new Array(count).fill(null);
+    let finished = 0;
+    let finishing = false;
+    
+    workers.forEach((worker, index) => {
+      worker.onmessage = event => {
+        const message = event.data;
+        if (message.type === 'batch') {
+          relayed[message.partition]++;
+          workers[message.partition].postMessage({ type: 'batch', data: message.data }, [message.data.buffer]);
+          return;
+        }
+        if (message.type === 'found') {
+          bestCost = bestCost === null ? message.totalCost : Math.min(bestCost, message.totalCost);
+        }
+        if (message.type === 'status') {
+          statuses[index] = message;
+          onProgress?.(relayed.reduce((sum, n) => sum + n, 0));
+        }
+        if (message.type === 'result' || message.type === 'cancelled' || message.type === 'error') {
+          results[index] = message.rawResult ?? null;
+          if (message.type === 'error' && !finishing) {
+            finishing = true;
+            workers.forEach(w => w.postMessage({ type: 'finish' }));
+          }
+          if (++finished === count) resolve(results.filter(result => result !== null));
+          return;
+        }
+        if (!finishing && settled()) {
+          finishing = true;
+          workers.forEach(w => w.postMessage({ type: 'finish' }));
+        }
+      };
+      worker.postMessage({
+        type: 'search',
+        start: options.start,
+        target: options.target,
+        hints: options.hints,
+        maxDepth: options.maxDepth,
+        mode,
+        occurrences: options.occurrences,
+        constraints: options.constraints,
+        config: options.config,
+        partition: { index, count }
+      });
+    });
+  });
+  
+  // Any engine can merge; ask the first one, then shut every worker down
+  const merged = await new Promise<any>(resolve => {
+    workers[0].onmessage = event => resolve(event.data);
+    workers[0].postMessage({ type: 'merge', results: finalResults });
+  });
+  workers.forEach(worker => worker.terminate());
+  return merged;
+}
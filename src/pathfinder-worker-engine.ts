@@ -1,22 +1,81 @@
 // Web Worker using WASM pathfinder engine
 
-import init, { PathfinderEngine } from './wasm-pathfinder/tanuki_pathfinder.js';
+import init, { PathfinderEngine, SolutionEnumerator, merge_results } from './wasm-pathfinder/tanuki_pathfinder.js';
 
 // Message types
+type SearchMode = 'heuristic' | 'bidirectional' | 'optimal';
+type OccurrenceMode = 'all' | 'single';
+
+// Search tunables; anything left out keeps the engine default
+interface SearchConfig {
+  cost_weight?: number;
+  best_attempts?: number;
+  distance_cache_capacity?: number;
+  decode_cache_capacity?: number;
+  heuristic?: 'weighted' | 'levenshtein' | 'multiset' | 'lcs' | 'zero';
+  normalization?: {
+    katakana_to_hiragana?: boolean;
+    half_width_to_full_width?: boolean;
+    nfc?: boolean;
+    small_kana_to_full_size?: boolean;
+  };
+  distance?: {
+    ngram_factor?: number;
+    longer_penalty?: number;
+    shorter_penalty?: number;
+  };
+  limits?: {
+    max_states_explored?: number;
+    max_states_stored?: number;
+    on_memory_limit?: 'prune' | 'stop';
+  };
+}
+
+// Limits on which hints a path may use, by hint name
+interface HintConstraints {
+  max_uses?: number;
+  max_uses_by_hint?: Record<string, number>;
+  required?: string[];
+  required_in_order?: boolean;
+  banned?: string[];
+}
+
+// This worker's share of a search split between several workers
+interface PartitionOptions {
+  index: number;
+  count: number;
+}
+
 interface WorkerMessage {
-  type: 'search' | 'cancel' | 'init';
+  type: 'search' | 'enumerate' | 'cancel' | 'init' | 'batch' | 'finish' | 'merge';
   start?: string;
   target?: string;
   maxDepth?: number;
   hints?: any[];
+  mode?: SearchMode;
+  occurrences?: OccurrenceMode;
+  constraints?: HintConstraints;
+  config?: SearchConfig;
+  maxSolutions?: number;
+  partition?: PartitionOptions;
+  // Serialized states from another partition ('batch')
+  data?: Uint8Array;
+  // Final results of every partition ('merge')
+  results?: any[];
 }
 
 interface WorkerResult {
-  type: 'result' | 'progress' | 'cancelled' | 'initialized' | 'error';
+  type: 'result' | 'progress' | 'solutions' | 'cancelled' | 'initialized' | 'error' | 'found' | 'batch' | 'status';
   found?: boolean;
   path?: string[];
   steps?: string[];
+  occurrences?: (number | null)[];
+  totalCost?: number;
   bestAttempts?: any[];
+  provenOptimal?: boolean;
+  terminatedReason?: 'found' | 'exhausted' | 'state_limit' | 'memory_limit';
+  solutions?: { path: string[]; steps: string[] }[];
+  totalSolutions?: number;
   progress?: number;
   progressPercentage?: number;
   estimatedTotal?: number;
@@ -28,6 +87,14 @@ interface WorkerResult {
     path: string[];
   };
   error?: string;
+  // Partitioned search: the partition a batch is for and its states, the
+  // partition's state, and its raw result for merging
+  partition?: number;
+  data?: Uint8Array;
+  idle?: boolean;
+  imported?: number;
+  frontierBound?: number;
+  rawResult?: any;
 }
 
 // Global state
@@ -35,6 +102,64 @@ let cancelled = false;
 let wasmInitialized = false;
 let currentEngine: PathfinderEngine | null = null;
 
+// Partitioned search: batches waiting to be imported, and whether the host
+// has asked for the final result
+const incomingBatches: Uint8Array[] = [];
+let finishRequested = false;
+
+// Checkpoints let a long search survive worker termination or a page reload
+const CHECKPOINT_DB_NAME = 'tanuki-pathfinder';
+const CHECKPOINT_STORE = 'checkpoints';
+const CHECKPOINT_INTERVAL_MS = 5000;
+
+// Stored states allowed unless the caller sets its own limit; well within
+// what the wasm heap can hold
+const DEFAULT_MAX_STATES_STORED = 1_000_000;
+
+function openCheckpointDb(): Promise<IDBDatabase> {
+  return new Promise((resolve, reject) => {
+    const request = indexedDB.open(CHECKPOINT_DB_NAME, 1);
+    request.onupgradeneeded = () => request.result.createObjectStore(CHECKPOINT_STORE);
+    request.onsuccess = () => resolve(request.result);
+    request.onerror = () => reject(request.error);
+  });
+}
+
+async function withCheckpointStore<T>(
+  mode: IDBTransactionMode,
+  action: (store: IDBObjectStore) => IDBRequest<T>
+): Promise<T | undefined> {
+  try {
+    const db = await openCheckpointDb();
+    return await new Promise<T>((resolve, reject) => {
+      const request = action(db.transaction(CHECKPOINT_STORE, mode).objectStore(CHECKPOINT_STORE));
+      request.onsuccess = () => resolve(request.result);
+      request.onerror = () => reject(request.error);
+    });
+  } catch (error) {
+    console.warn('Checkpoint storage unavailable:', error);
+    return undefined;
+  }
+}
+
+// Whole hints, not just their names, so editing a hint's operation, cost or
+// group never resumes a checkpoint made with the old one
+function checkpointKey(start: string, target: string, hints: any[], maxDepth: number, mode: SearchMode, occurrences: OccurrenceMode, constraints: HintConstraints, config: SearchConfig): string {
+  return JSON.stringify([mode, occurrences, constraints, config, maxDepth, start, target, hints]);
+}
+
+function loadCheckpoint(key: string): Promise<Uint8Array | undefined> {
+  return withCheckpointStore('readonly', store => store.get(key));
+}
+
+async function saveCheckpoint(key: string, state: Uint8Array): Promise<void> {
+  await withCheckpointStore('readwrite', store => store.put(state, key));
+}
+
+async function deleteCheckpoint(key: string): Promise<void> {
+  await withCheckpointStore('readwrite', store => store.delete(key));
+}
+
 // Initialize WASM
 async function initializeWasm() {
   if (wasmInitialized) return;
@@ -43,12 +168,47 @@ async function initializeWasm() {
   wasmInitialized = true;
 }
 
+function createEngine(
+  start: string,
+  target: string,
+  hintsJson: string,
+  maxDepth: number,
+  mode: SearchMode,
+  occurrences: OccurrenceMode,
+  constraints: HintConstraints,
+  config: SearchConfig
+): PathfinderEngine {
+  const limits = { max_states_stored: DEFAULT_MAX_STATES_STORED, ...config.limits };
+  const engine = PathfinderEngine.with_config(start, target, hintsJson, { ...config, limits, mode, max_depth: maxDepth });
+  engine.set_occurrence_mode(occurrences);
+  engine.set_constraints(constraints);
+  return engine;
+}
+
+function searchResultMessage(result: any): WorkerResult {
+  return {
+    type: 'result',
+    found: result.found,
+    path: result.path || [],
+    steps: result.steps || [],
+    occurrences: result.occurrences || [],
+    totalCost: result.total_cost,
+    bestAttempts: result.best_attempts || [],
+    provenOptimal: result.proven_optimal,
+    terminatedReason: result.terminated_reason
+  };
+}
+
 // Run search using WASM engine
 async function runSearch(
   start: string,
   target: string,
   hints: any[],
-  maxDepth: number
+  maxDepth: number,
+  mode: SearchMode,
+  occurrences: OccurrenceMode,
+  constraints: HintConstraints,
+  config: SearchConfig
 ): Promise<WorkerResult> {
   // Check for trivial case
   if (start === target) {
@@ -57,14 +217,31 @@ async function runSearch(
       found: true,
       path: [],
       steps: [start],
+      occurrences: [],
+      totalCost: 0,
       bestAttempts: []
     };
   }
   
+  // Resume from a checkpoint of the same search if one exists
+  const key = checkpointKey(start, target, hints, maxDepth, mode, occurrences, constraints, config);
+  const checkpoint = await loadCheckpoint(key);
+  let resumed: PathfinderEngine | null = null;
+  if (checkpoint) {
+    try {
+      resumed = PathfinderEngine.from_state(checkpoint);
+      console.log('Resuming search from checkpoint');
+    } catch (error) {
+      console.warn('Discarding unreadable checkpoint:', error);
+      await deleteCheckpoint(key);
+    }
+  }
+  
   // Create new engine
+  let engine: PathfinderEngine;
   try {
-    const hintsJson = JSON.stringify(hints);
-    currentEngine = new PathfinderEngine(start, target, hintsJson, maxDepth);
+    engine = resumed ?? createEngine(start, target, JSON.stringify(hints), maxDepth, mode, occurrences, constraints, config);
+    currentEngine = engine;
   } catch (error) {
     console.error('Failed to create PathfinderEngine:', error);
     return {
@@ -76,22 +253,18 @@ async function runSearch(
   const ITERATIONS_PER_BATCH = 100;
   const UPDATE_INTERVAL_MS = 100;
   let lastUpdateTime = Date.now();
+  let lastCheckpointTime = Date.now();
   
   // Run search loop
-  while (!currentEngine.is_complete() && !cancelled) {
+  while (!engine.is_complete() && !cancelled) {
     // Run batch of iterations
-    const result = currentEngine.run_iterations(ITERATIONS_PER_BATCH);
+    const result = engine.run_iterations(ITERATIONS_PER_BATCH);
     
     // Check if we have a final result
     if (result.found !== undefined) {
+      await deleteCheckpoint(key);
       // Found the target
-      return {
-        type: 'result',
-        found: result.found,
-        path: result.path || [],
-        steps: result.steps || [],
-        bestAttempts: result.best_attempts || []
-      };
+      return searchResultMessage(result);
     }
     
     // Send progress update
@@ -112,30 +285,168 @@ async function runSearch(
       });
       lastUpdateTime = now;
       
+      // Save a checkpoint every few seconds
+      if (now - lastCheckpointTime >= CHECKPOINT_INTERVAL_MS) {
+        await saveCheckpoint(key, engine.export_state());
+        lastCheckpointTime = now;
+      }
+      
       // Yield to event loop
       await new Promise(resolve => setTimeout(resolve, 0));
     }
   }
   
+  // The search is over either way, so the checkpoint is no longer needed
+  await deleteCheckpoint(key);
+  
   // Get final result
   if (cancelled) {
-    const finalResult = currentEngine.get_result();
+    const finalResult = engine.get_result();
     return {
       type: 'cancelled',
       bestAttempts: finalResult.best_attempts || []
     };
   } else {
-    const finalResult = currentEngine.get_result();
+    const finalResult = engine.get_result();
     return {
       type: 'result',
       found: false,
       path: [],
       steps: [],
-      bestAttempts: finalResult.best_attempts || []
+      bestAttempts: finalResult.best_attempts || [],
+      terminatedReason: finalResult.terminated_reason
     };
   }
 }
 
+// Run one partition of a search split between workers. States owned by
+// other partitions are posted as 'batch' messages for the host to relay, and
+// a 'status' after every round tells the host whether this partition is idle
+// and how many batches it has imported. A found path is posted as 'found' and
+// the search goes on, since another partition may hold a cheaper one, until
+// the host sends 'finish'. Partitions are not checkpointed.
+async function runPartition(
+  start: string,
+  target: string,
+  hints: any[],
+  maxDepth: number,
+  mode: SearchMode,
+  occurrences: OccurrenceMode,
+  constraints: HintConstraints,
+  config: SearchConfig,
+  partition: PartitionOptions
+): Promise<WorkerResult> {
+  let engine: PathfinderEngine;
+  try {
+    engine = createEngine(start, target, JSON.stringify(hints), maxDepth, mode, occurrences, constraints, config);
+    engine.set_partition(partition.index, partition.count);
+    currentEngine = engine;
+  } catch (error) {
+    console.error('Failed to create PathfinderEngine:', error);
+    return {
+      type: 'error',
+      error: error instanceof Error ? error.message : 'Failed to create engine'
+    };
+  }
+  
+  const ITERATIONS_PER_BATCH = 100;
+  const IDLE_WAIT_MS = 10;
+  let imported = 0;
+  let best: any = null;
+  
+  while (!cancelled && !finishRequested) {
+    // A batch that cannot be read ends this partition with an error, which
+    // has the host finish the other partitions instead of waiting for it
+    try {
+      for (const data of incomingBatches.splice(0)) {
+        engine.import_states(data);
+        imported++;
+      }
+    } catch (error) {
+      console.error('Failed to import state batch:', error);
+      return {
+        type: 'error',
+        partition: partition.index,
+        error: error instanceof Error ? error.message : 'Failed to import state batch'
+      };
+    }
+    
+    const idle = engine.is_complete();
+    if (!idle) {
+      const result = engine.run_iterations(ITERATIONS_PER_BATCH);
+      if (result.found !== undefined && (!best || result.total_cost < best.total_cost)) {
+        best = result;
+        self.postMessage({ ...searchResultMessage(result), type: 'found', partition: partition.index });
+      }
+    }
+    
+    for (const batch of engine.take_outgoing()) {
+      self.postMessage({ type: 'batch', partition: batch.partition, data: batch.data }, { transfer: [batch.data.buffer] });
+    }
+    self.postMessage({
+      type: 'status',
+      partition: partition.index,
+      idle: engine.is_complete(),
+      imported,
+      frontierBound: engine.frontier_bound()
+    });
+    
+    // Yield to event loop, so batches and 'finish' can arrive
+    await new Promise(resolve => setTimeout(resolve, idle ? IDLE_WAIT_MS : 0));
+  }
+  
+  // A found result keeps its path; the engine's totals have moved on since
+  const rawResult = best ?? engine.get_result();
+  return { type: cancelled ? 'cancelled' : 'result', partition: partition.index, rawResult };
+}
+
+// Enumerate every hint sequence up to maxDepth, streaming solutions as they complete
+async function runEnumeration(
+  start: string,
+  target: string,
+  hints: any[],
+  maxDepth: number,
+  maxSolutions: number
+): Promise<WorkerResult> {
+  let enumerator: SolutionEnumerator;
+  try {
+    enumerator = new SolutionEnumerator(start, target, JSON.stringify(hints), maxDepth, maxSolutions);
+  } catch (error) {
+    console.error('Failed to create SolutionEnumerator:', error);
+    return {
+      type: 'error',
+      error: error instanceof Error ? error.message : 'Failed to create enumerator'
+    };
+  }
+  
+  const ITERATIONS_PER_BATCH = 100;
+  let totalSolutions = 0;
+  
+  while (!enumerator.is_complete() && !cancelled) {
+    const update = enumerator.run_iterations(ITERATIONS_PER_BATCH);
+    totalSolutions = update.total_solutions;
+    
+    if (update.solutions.length > 0) {
+      self.postMessage({
+        type: 'solutions',
+        solutions: update.solutions,
+        totalSolutions,
+        progress: update.states_explored,
+        maxDepthReached: update.current_depth
+      });
+    }
+    
+    // Yield to event loop
+    await new Promise(resolve => setTimeout(resolve, 0));
+  }
+  
+  return {
+    type: cancelled ? 'cancelled' : 'result',
+    found: totalSolutions > 0,
+    totalSolutions
+  };
+}
+
 // Message handler
 self.addEventListener('message', async (event: MessageEvent<WorkerMessage>) => {
   const { type } = event.data;
@@ -158,9 +469,30 @@ self.addEventListener('message', async (event: MessageEvent<WorkerMessage>) => {
     return;
   }
   
+  if (type === 'batch') {
+    if (event.data.data) {
+      incomingBatches.push(event.data.data);
+    }
+    return;
+  }
+  
+  if (type === 'finish') {
+    finishRequested = true;
+    return;
+  }
+  
+  if (type === 'merge') {
+    try {
+      self.postMessage(searchResultMessage(merge_results(event.data.results || [])));
+    } catch (error) {
+      self.postMessage({ type: 'error', error: error instanceof Error ? error.message : String(error) });
+    }
+    return;
+  }
+  
   if (type === 'search') {
     cancelled = false;
-    const { start, target, maxDepth, hints } = event.data;
+    const { start, target, maxDepth, hints, mode, occurrences, constraints, config, partition } = event.data;
     
     console.log('Search request received:', { start, target, maxDepth, hintsCount: hints?.length });
     
@@ -169,8 +501,30 @@ self.addEventListener('message', async (event: MessageEvent<WorkerMessage>) => {
       return;
     }
     
-    const result = await runSearch(start, target, hints, maxDepth || 20);
+    if (partition) {
+      finishRequested = false;
+      incomingBatches.length = 0;
+      const result = await runPartition(start, target, hints, maxDepth || 20, mode || 'heuristic', occurrences || 'all', constraints || {}, config || {}, partition);
+      self.postMessage(result);
+      return;
+    }
+    
+    const result = await runSearch(start, target, hints, maxDepth || 20, mode || 'heuristic', occurrences || 'all', constraints || {}, config || {});
     console.log('Search result:', result);
     self.postMessage(result);
+    return;
+  }
+  
+  if (type === 'enumerate') {
+    cancelled = false;
+    const { start, target, maxDepth, hints, maxSolutions } = event.data;
+    
+    if (!start || !target || !hints) {
+      console.error('Missing required enumeration parameters');
+      return;
+    }
+    
+    const result = await runEnumeration(start, target, hints, maxDepth || 8, maxSolutions || 0);
+    self.postMessage(result);
   }
 });
\ No newline at end of file
@@ -14,6 +14,10 @@ export interface Hint {
   operation: HintOperation;
   description: string;
   group: HintGroup;
+  // Search cost of one application; defaults to the group's cost
+  cost?: number;
+  // 'single' makes the pathfinder rewrite one occurrence of the target per step
+  occurrences?: 'all' | 'single';
 }
 
 export interface CipherResult {
@@ -0,0 +1,80 @@
+// Deep search benchmark.
+//
+// Run with `cargo bench --bench deep_search`. Reports wall time, throughput
+// and peak heap usage of the engine for a fixed exploration budget, using the
+// full hint list from the TypeScript data.
+//
+// Interning the search texts, measured on the same machine (before -> after):
+//   めがね…  14.6k -> 14.4k states/s, peak 129543 -> 120213 KiB
+//   きなぞ…  23.4k -> 20.7k states/s, peak  76426 ->  71587 KiB
+// Throughput stayed within the run-to-run noise of about 15%.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use tanuki_pathfinder::{Pathfinder, SearchMode, SearchStep};
+
+// Allocator wrapper that tracks current and peak heap usage
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const HINTS: &str = include_str!("../fixtures/hints.json");
+
+// Questions encoded from "なぞときはたのしい" with eight remove hints each
+const DEEP_PUZZLES: [(&str, &str); 2] = [
+    ("めがねなぞとのきよはたぺつのしやぺめがねのいなこ", "なぞときはたのしい"),
+    ("きなぞすれすとれらきふすはらたじきじのやしふおいおらやふ", "なぞときはたのしい"),
+];
+
+const STATE_BUDGET: usize = 50_000;
+
+fn main() {
+    for (start, target) in DEEP_PUZZLES {
+        let baseline = CURRENT.load(Ordering::Relaxed);
+        PEAK.store(baseline, Ordering::Relaxed);
+        let started = Instant::now();
+        
+        let mut engine = Pathfinder::from_json(start, target, HINTS, 12, SearchMode::Heuristic).unwrap();
+        let mut found = false;
+        while !found && !engine.is_complete() && engine.progress().states_explored < STATE_BUDGET {
+            found = matches!(engine.step(1000), SearchStep::Found(_));
+        }
+        let elapsed = started.elapsed();
+        let progress = engine.progress();
+        let caches = engine.cache_stats();
+        
+        println!(
+            "{} -> {}: found={} explored={} queued={} time={:?} throughput={:.0} states/s peak={} KiB distance cache {}/{} decode cache {}/{} (hits/misses)",
+            start,
+            target,
+            found,
+            progress.states_explored,
+            progress.queue_size,
+            elapsed,
+            progress.states_explored as f64 / elapsed.as_secs_f64(),
+            (PEAK.load(Ordering::Relaxed) - baseline) / 1024,
+            caches.distance.hits,
+            caches.distance.misses,
+            caches.decode.hits,
+            caches.decode.misses
+        );
+    }
+}
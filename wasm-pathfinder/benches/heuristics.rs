@@ -0,0 +1,66 @@
+// Heuristic comparison benchmark.
+//
+// Run with `cargo bench --bench heuristics`. Solves every puzzle in
+// `fixtures/puzzles.json` with each heuristic, in heuristic mode and within
+// a fixed exploration budget, and reports the states each one explored.
+use std::time::Instant;
+
+use serde::Deserialize;
+use tanuki_pathfinder::{parse_hints, HeuristicKind, Pathfinder, SearchConfig, SearchStep};
+
+const HINTS: &str = include_str!("../fixtures/hints.json");
+const PUZZLES: &str = include_str!("../fixtures/puzzles.json");
+
+const HEURISTICS: [HeuristicKind; 5] = [
+    HeuristicKind::Weighted,
+    HeuristicKind::Levenshtein,
+    HeuristicKind::Multiset,
+    HeuristicKind::Lcs,
+    HeuristicKind::Zero,
+];
+
+const STATE_BUDGET: usize = 20_000;
+
+#[derive(Deserialize)]
+struct Puzzle {
+    question: String,
+    answer: String,
+    // Hints the question was encoded with
+    depth: usize,
+}
+
+fn main() {
+    let hints = parse_hints(HINTS).unwrap();
+    let puzzles: Vec<Puzzle> = serde_json::from_str(PUZZLES).unwrap();
+    
+    for heuristic in HEURISTICS {
+        let started = Instant::now();
+        let mut solved = 0;
+        let mut total_explored = 0;
+        let mut explored_per_puzzle = Vec::new();
+        
+        for puzzle in &puzzles {
+            let config = SearchConfig { heuristic, max_depth: puzzle.depth + 2, ..SearchConfig::default() };
+            let mut engine = Pathfinder::with_config(&puzzle.question, &puzzle.answer, hints.clone(), config).unwrap();
+            let mut found = false;
+            while !found && !engine.is_complete() && engine.progress().states_explored < STATE_BUDGET {
+                found = matches!(engine.step(1000), SearchStep::Found(_));
+            }
+            
+            let explored = engine.progress().states_explored;
+            solved += usize::from(found);
+            total_explored += explored;
+            explored_per_puzzle.push(if found { explored.to_string() } else { format!("({})", explored) });
+        }
+        
+        println!(
+            "{:?}: solved {}/{} explored={} time={:?}\n  {}",
+            heuristic,
+            solved,
+            puzzles.len(),
+            total_explored,
+            started.elapsed(),
+            explored_per_puzzle.join(" ")
+        );
+    }
+}
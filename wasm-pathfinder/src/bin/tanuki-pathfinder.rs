@@ -0,0 +1,238 @@
+// Command-line pathfinder: reads a hints JSON file and prints the hint path
+// that turns the start text into the target. With `--solve` and no target it
+// applies every hint in the file once and ranks the possible answers.
+use std::process::ExitCode;
+
+use tanuki_pathfinder::{parse_hints, solve, Dictionary, DictionaryMode, HintConstraints, LimitAction, Normalization, OccurrenceMode, Pathfinder, SearchConfig, SearchResult, TerminatedReason};
+
+const USAGE: &str = "Usage: tanuki-pathfinder <hints.json> <start> <target> | --solve <hints.json> <question> [--max-depth N] [--max-explored N] [--max-stored N] [--stop-at-limit] [--mode heuristic|bidirectional|optimal] [--heuristic weighted|levenshtein|multiset|lcs|zero] [--occurrences all|single] [--no-normalize] [--small-kana] [--dictionary words.txt|words.fst] [--dictionary-mode require|prefer] [--max-uses N] [--require a,b] [--require-in-order] [--ban a,b] [--parallel]";
+
+struct Args {
+    hints_path: String,
+    start: String,
+    // None when solving for an unknown answer
+    target: Option<String>,
+    config: SearchConfig,
+    occurrences: OccurrenceMode,
+    dictionary_path: Option<String>,
+    dictionary_mode: DictionaryMode,
+    constraints: HintConstraints,
+    parallel: bool,
+}
+
+// Hint names from a comma-separated list
+fn hint_names(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|name| !name.is_empty()).map(str::to_string).collect()
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut positional = Vec::new();
+    let mut config = SearchConfig::default();
+    let mut occurrences = OccurrenceMode::All;
+    let mut dictionary_path = None;
+    let mut dictionary_mode = DictionaryMode::Require;
+    let mut constraints = HintConstraints::default();
+    let mut solve = false;
+    let mut parallel = false;
+    
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-depth" => {
+                let value = args.next().ok_or("--max-depth needs a value")?;
+                config.max_depth = value.parse().map_err(|_| format!("Invalid --max-depth: {}", value))?;
+            },
+            "--max-explored" => {
+                let value = args.next().ok_or("--max-explored needs a value")?;
+                config.limits.max_states_explored = Some(value.parse().map_err(|_| format!("Invalid --max-explored: {}", value))?);
+            },
+            "--max-stored" => {
+                let value = args.next().ok_or("--max-stored needs a value")?;
+                config.limits.max_states_stored = Some(value.parse().map_err(|_| format!("Invalid --max-stored: {}", value))?);
+            },
+            "--stop-at-limit" => config.limits.on_memory_limit = LimitAction::Stop,
+            "--mode" => {
+                let value = args.next().ok_or("--mode needs a value")?;
+                config.mode = value.parse()?;
+            },
+            "--heuristic" => {
+                let value = args.next().ok_or("--heuristic needs a value")?;
+                config.heuristic = value.parse()?;
+            },
+            "--occurrences" => {
+                let value = args.next().ok_or("--occurrences needs a value")?;
+                occurrences = value.parse()?;
+            },
+            "--dictionary" => {
+                dictionary_path = Some(args.next().ok_or("--dictionary needs a value")?);
+            },
+            "--dictionary-mode" => {
+                let value = args.next().ok_or("--dictionary-mode needs a value")?;
+                dictionary_mode = value.parse()?;
+            },
+            "--max-uses" => {
+                let value = args.next().ok_or("--max-uses needs a value")?;
+                constraints.max_uses = Some(value.parse().map_err(|_| format!("Invalid --max-uses: {}", value))?);
+            },
+            "--require" => {
+                constraints.required = hint_names(&args.next().ok_or("--require needs a value")?);
+            },
+            "--ban" => {
+                constraints.banned = hint_names(&args.next().ok_or("--ban needs a value")?);
+            },
+            "--require-in-order" => constraints.required_in_order = true,
+            "--solve" => solve = true,
+            "--parallel" if cfg!(feature = "parallel") => parallel = true,
+            "--parallel" => return Err("--parallel needs a build with the parallel feature".to_string()),
+            "--no-normalize" => config.normalization = Normalization::none(),
+            "--small-kana" => config.normalization.small_kana_to_full_size = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option: {}", arg)),
+            _ => positional.push(arg),
+        }
+    }
+    
+    let (hints_path, start, target) = match (solve, <[String; 3]>::try_from(positional)) {
+        (false, Ok([hints_path, start, target])) => (hints_path, start, Some(target)),
+        (true, Err(positional)) if positional.len() == 2 => {
+            let [hints_path, start]: [String; 2] = positional.try_into().map_err(|_| USAGE.to_string())?;
+            (hints_path, start, None)
+        },
+        _ => return Err(USAGE.to_string()),
+    };
+    Ok(Args { hints_path, start, target, config, occurrences, dictionary_path, dictionary_mode, constraints, parallel })
+}
+
+// `.fst` files hold a prebuilt FST set, anything else a plain word list
+fn load_dictionary(path: &str) -> Result<Dictionary, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    if path.ends_with(".fst") {
+        Dictionary::from_fst(bytes).map_err(|e| e.to_string())
+    } else {
+        let words = String::from_utf8(bytes).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Ok(Dictionary::from_word_list(&words))
+    }
+}
+
+fn run_solver(question: &str, hints_json: &str, dictionary: Option<&Dictionary>) -> ExitCode {
+    let hints = match parse_hints(hints_json) {
+        Ok(hints) => hints,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    
+    let result = solve(question, &hints, dictionary);
+    if result.candidates.is_empty() {
+        println!("No order applies every hint ({} orders got stuck)", result.blocked_orderings);
+        return ExitCode::FAILURE;
+    }
+    
+    for candidate in result.candidates.iter().take(10) {
+        let mark = if candidate.in_dictionary { " (word)" } else if candidate.segmentable { " (words)" } else { "" };
+        println!(
+            "{} score {:.2}{}: {}",
+            candidate.text,
+            candidate.score,
+            mark,
+            candidate.path.join(" → ")
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+fn run_search(engine: &mut Pathfinder, parallel: bool) -> SearchResult {
+    if parallel {
+        engine.run_parallel()
+    } else {
+        engine.run()
+    }
+}
+
+#[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+fn run_search(engine: &mut Pathfinder, _parallel: bool) -> SearchResult {
+    engine.run()
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(2);
+        }
+    };
+    
+    let hints_json = match std::fs::read_to_string(&args.hints_path) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", args.hints_path, e);
+            return ExitCode::from(2);
+        }
+    };
+    
+    let dictionary = match args.dictionary_path.as_deref().map(load_dictionary).transpose() {
+        Ok(dictionary) => dictionary,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(2);
+        }
+    };
+    
+    let target = match &args.target {
+        Some(target) => target,
+        None => return run_solver(&args.start, &hints_json, dictionary.as_ref()),
+    };
+    
+    let engine = parse_hints(&hints_json).and_then(|hints| {
+        Pathfinder::with_config(&args.start, target, hints, args.config)
+    });
+    let mut engine = match engine {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    
+    engine.set_occurrence_mode(args.occurrences);
+    if let Err(e) = engine.set_constraints(args.constraints) {
+        eprintln!("{}", e);
+        return ExitCode::from(2);
+    }
+    
+    if let Some(dictionary) = dictionary {
+        engine.set_dictionary(dictionary, args.dictionary_mode);
+    }
+    
+    let result = run_search(&mut engine, args.parallel);
+    if !result.found {
+        let reason = match result.terminated_reason {
+            TerminatedReason::StateLimit => ", stopped at the state limit",
+            TerminatedReason::MemoryLimit => ", stopped at the memory limit",
+            TerminatedReason::Found | TerminatedReason::Exhausted => "",
+        };
+        println!("No path found after exploring {} states{}", result.total_states_explored, reason);
+        for attempt in result.best_attempts.iter().take(5) {
+            println!("  {} (distance {:.2}): {}", attempt.text, attempt.distance, attempt.path.join(" → "));
+        }
+        return ExitCode::FAILURE;
+    }
+    
+    println!("path: {}", result.path.join(" → "));
+    println!("steps:");
+    println!("  {}", result.steps[0]);
+    for ((hint, step), occurrence) in result.path.iter().zip(&result.steps[1..]).zip(&result.occurrences) {
+        match occurrence {
+            Some(position) => println!("  {} ({} at {})", step, hint, position),
+            None => println!("  {} ({})", step, hint),
+        }
+    }
+    println!(
+        "cost {:.1}, explored {} states{}",
+        result.total_cost,
+        result.total_states_explored,
+        if result.proven_optimal { ", path is proven cheapest" } else { "" }
+    );
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::hint::Hint;
+use crate::kana::{nfc, voicing_variants};
+
+// Per-hint character limits used by the optimal search lower bound
+#[derive(Debug, Default)]
+pub(crate) struct HintBounds {
+    consumable: HashSet<char>,
+    producible: HashSet<char>,
+    max_target_chars: usize,
+    max_replacement_chars: usize,
+}
+
+impl HintBounds {
+    pub(crate) fn new(hints: &[Hint]) -> Self {
+        let mut bounds = HintBounds::default();
+        
+        for hint in hints {
+            // A hint that ignores voicing consumes every marked form of its kana
+            let target = nfc(hint.operation.target());
+            let target_chars: HashSet<char> = if hint.ignore_voicing {
+                target.chars().flat_map(voicing_variants).collect()
+            } else {
+                target.chars().collect()
+            };
+            bounds.max_target_chars = bounds.max_target_chars.max(target_chars.len());
+            bounds.consumable.extend(target_chars);
+            
+            let replacement_chars: HashSet<char> = hint.operation.replacement().chars().collect();
+            bounds.max_replacement_chars = bounds.max_replacement_chars.max(replacement_chars.len());
+            bounds.producible.extend(replacement_chars);
+        }
+        
+        bounds
+    }
+    
+    // Admissible lower bound on the number of hints needed to reach the target,
+    // or None when no sequence of hints can reach it.
+    //
+    // A hint only changes the counts of characters in its target or replacement.
+    // Every character that occurs more often than in the target has to be
+    // consumed by some hint target, and every missing one has to come from some
+    // replacement, so one application fixes at most as many distinct characters
+    // as the widest hint target (or replacement) contains.
+    pub(crate) fn lower_bound(&self, text: &str, target: &str) -> Option<usize> {
+        if text == target {
+            return Some(0);
+        }
+        
+        let mut counts: HashMap<char, i64> = HashMap::new();
+        for c in text.chars() {
+            *counts.entry(c).or_insert(0) += 1;
+        }
+        for c in target.chars() {
+            *counts.entry(c).or_insert(0) -= 1;
+        }
+        
+        let mut excess = 0usize;
+        let mut deficit = 0usize;
+        for (c, diff) in counts {
+            if diff > 0 {
+                if !self.consumable.contains(&c) {
+                    return None;
+                }
+                excess += 1;
+            } else if diff < 0 {
+                if !self.producible.contains(&c) {
+                    return None;
+                }
+                deficit += 1;
+            }
+        }
+        
+        let consume_steps = if excess > 0 { excess.div_ceil(self.max_target_chars) } else { 0 };
+        let produce_steps = if deficit > 0 { deficit.div_ceil(self.max_replacement_chars) } else { 0 };
+        Some(consume_steps.max(produce_steps).max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use crate::hint::HintOperation;
+    
+    // Fewest hint applications from every text reachable from the start to
+    // the target, found by breadth-first search
+    fn true_distances(start: &str, target: &str, hints: &[Hint], max_depth: usize) -> Vec<(String, usize)> {
+        let mut depth: HashMap<String, usize> = HashMap::from([(start.to_string(), 0)]);
+        let mut queue = VecDeque::from([start.to_string()]);
+        let mut edges: Vec<(String, String)> = Vec::new();
+        while let Some(text) = queue.pop_front() {
+            if depth[&text] == max_depth {
+                continue;
+            }
+            for next in hints.iter().filter_map(|hint| hint.apply(&text)) {
+                edges.push((next.clone(), text.clone()));
+                if !depth.contains_key(&next) {
+                    depth.insert(next.clone(), depth[&text] + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        
+        // Walk the edges backwards from the target
+        let mut remaining: HashMap<String, usize> = HashMap::from([(target.to_string(), 0)]);
+        let mut queue = VecDeque::from([target.to_string()]);
+        while let Some(text) = queue.pop_front() {
+            for (_, previous) in edges.iter().filter(|(next, _)| *next == text) {
+                if !remaining.contains_key(previous) {
+                    remaining.insert(previous.clone(), remaining[&text] + 1);
+                    queue.push_back(previous.clone());
+                }
+            }
+        }
+        remaining.into_iter().collect()
+    }
+    
+    #[test]
+    fn bound_never_exceeds_the_remaining_steps() {
+        let hints = [
+            Hint::new("a", HintOperation::replace("か", "さ")),
+            Hint::new("b", HintOperation::replace("き", "く")),
+            Hint::new("c", HintOperation::remove("く")),
+            Hint::new("d", HintOperation::replace("かき", "けけ")),
+            Hint::new("e", HintOperation::replace("けけ", "さ")),
+            Hint::new("f", HintOperation::remove("さい")),
+        ];
+        let bounds = HintBounds::new(&hints);
+        let distances = true_distances("かきさい", "さ", &hints, 6);
+        assert!(distances.len() > 5);
+        for (text, steps) in distances {
+            let bound = bounds.lower_bound(&text, "さ").unwrap();
+            assert!(bound <= steps, "{} needs {} steps but the bound is {}", text, steps, bound);
+        }
+    }
+    
+    #[test]
+    fn unreachable_characters_have_no_bound() {
+        let bounds = HintBounds::new(&[Hint::new("a", HintOperation::replace("か", "さ"))]);
+        assert_eq!(bounds.lower_bound("かか", "さ"), Some(1));
+        // Nothing removes い
+        assert_eq!(bounds.lower_bound("かい", "さ"), None);
+        // Nothing produces す
+        assert_eq!(bounds.lower_bound("か", "す"), None);
+        assert_eq!(bounds.lower_bound("い", "い"), Some(0));
+    }
+    
+    #[test]
+    fn voicing_variants_are_consumable() {
+        let remove_ka = Hint::new("a", HintOperation::remove("か"));
+        assert_eq!(HintBounds::new(std::slice::from_ref(&remove_ka)).lower_bound("が", ""), None);
+        
+        let bounds = HintBounds::new(&[Hint { ignore_voicing: true, ..remove_ka }]);
+        assert_eq!(bounds.lower_bound("が", ""), Some(1));
+        assert_eq!(bounds.lower_bound("かが", ""), Some(1));
+        // は has no relation to か
+        assert_eq!(bounds.lower_bound("ぱ", ""), None);
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use serde::{Serialize, Deserialize};
+
+// Usage counters of one cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    // Set on every hit; the clock hand clears it once before evicting
+    referenced: bool,
+}
+
+// Bounded cache with CLOCK eviction, an approximation of LRU that needs no
+// reordering on hits. Keys are small and copied, such as tuples of text ids.
+// A capacity of 0 disables caching.
+pub(crate) struct ClockCache<K, V> {
+    index: HashMap<K, usize>,
+    entries: Vec<Entry<K, V>>,
+    hand: usize,
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl<K: Copy + Eq + Hash, V> ClockCache<K, V> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        ClockCache {
+            index: HashMap::new(),
+            entries: Vec::new(),
+            hand: 0,
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+    
+    pub(crate) fn get(&mut self, key: K) -> Option<&V> {
+        match self.index.get(&key) {
+            Some(&slot) => {
+                self.hits += 1;
+                let entry = &mut self.entries[slot];
+                entry.referenced = true;
+                Some(&entry.value)
+            },
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+    
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some(&slot) = self.index.get(&key) {
+            self.entries[slot].value = value;
+            return;
+        }
+        
+        let entry = Entry { key, value, referenced: false };
+        if self.entries.len() < self.capacity {
+            self.index.insert(key, self.entries.len());
+            self.entries.push(entry);
+            return;
+        }
+        
+        // Sweep past recently used entries, giving each a second chance
+        while self.entries[self.hand].referenced {
+            self.entries[self.hand].referenced = false;
+            self.hand = (self.hand + 1) % self.entries.len();
+        }
+        let slot = self.hand;
+        self.index.remove(&self.entries[slot].key);
+        self.index.insert(key, slot);
+        self.entries[slot] = entry;
+        self.hand = (slot + 1) % self.entries.len();
+    }
+    
+    // Rewrite every key with `f`, dropping the entries it returns None for.
+    // The others keep their reference bits and their order around the clock.
+    pub(crate) fn remap_keys(&mut self, f: impl Fn(K) -> Option<K>) {
+        let old_hand = self.hand;
+        self.hand = 0;
+        self.index.clear();
+        for (slot, entry) in std::mem::take(&mut self.entries).into_iter().enumerate() {
+            let Some(key) = f(entry.key) else {
+                continue;
+            };
+            if slot < old_hand {
+                self.hand += 1;
+            }
+            self.index.insert(key, self.entries.len());
+            self.entries.push(Entry { key, ..entry });
+        }
+        if self.hand == self.entries.len() {
+            self.hand = 0;
+        }
+    }
+    
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn evicts_entries_that_were_not_used_again() {
+        let mut cache = ClockCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.get("a"), Some(&1));
+        
+        // "b" was never read, so it goes first
+        cache.insert("c", 3);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(&1));
+        assert_eq!(cache.get("c"), Some(&3));
+        
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (3, 1, 2));
+    }
+    
+    #[test]
+    fn remapped_keys_keep_their_values() {
+        let mut cache = ClockCache::new(3);
+        cache.insert((0, 1), "a");
+        cache.insert((1, 2), "b");
+        cache.insert((2, 0), "c");
+        
+        // Id 1 is dropped, 2 becomes 1
+        cache.remap_keys(|(x, y): (u32, u32)| {
+            let remap = |id| match id {
+                0 => Some(0),
+                2 => Some(1),
+                _ => None,
+            };
+            Some((remap(x)?, remap(y)?))
+        });
+        assert_eq!(cache.get((1, 0)), Some(&"c"));
+        assert_eq!(cache.get((0, 1)), None);
+        assert_eq!(cache.stats().entries, 1);
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Serialize, Deserialize};
+
+use crate::distance::DistanceWeights;
+use crate::engine::SearchMode;
+use crate::heuristic::HeuristicKind;
+use crate::kana::Normalization;
+
+// Everything that tunes a search. Every field has a default, so a partial
+// JSON object is enough: `{"mode": "optimal", "max_depth": 8}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    // Search strategy
+    pub mode: SearchMode,
+    // Longest hint path to consider
+    pub max_depth: usize,
+    pub normalization: Normalization,
+    // Weight of the path cost next to the distance in the heuristic modes
+    pub cost_weight: f64,
+    // Closest misses kept for the result when the target is not found
+    pub best_attempts: usize,
+    // Entries kept by the distance and hint application caches; a full cache
+    // evicts CLOCK-style, giving recently used entries a second chance, and
+    // 0 turns a cache off
+    pub distance_cache_capacity: usize,
+    pub decode_cache_capacity: usize,
+    // Distance estimate that orders the heuristic modes
+    pub heuristic: HeuristicKind,
+    // Weights of the `weighted` heuristic
+    pub distance: DistanceWeights,
+    pub limits: SearchLimits,
+}
+
+// What the engine does once it stores `max_states_stored` states
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitAction {
+    // Drop the worst frontier states, SMA*-style, and keep searching; paths
+    // through dropped states may be missed, so optimal results are no
+    // longer proven
+    #[default]
+    Prune,
+    // End the search
+    Stop,
+}
+
+// Bounds that keep hard searches from exhausting memory. A search that hits
+// one ends with its best attempts and a `terminated_reason` saying which.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchLimits {
+    // States to expand before giving up
+    pub max_states_explored: Option<usize>,
+    // Reached states to keep in memory, counting both search directions
+    pub max_states_stored: Option<usize>,
+    pub on_memory_limit: LimitAction,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            mode: SearchMode::Heuristic,
+            max_depth: 20,
+            normalization: Normalization::default(),
+            cost_weight: 0.1,
+            best_attempts: 30,
+            distance_cache_capacity: 10_000,
+            decode_cache_capacity: 100_000,
+            heuristic: HeuristicKind::Weighted,
+            distance: DistanceWeights::default(),
+            limits: SearchLimits::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Pathfinder, TerminatedReason};
+    
+    #[test]
+    fn missing_fields_take_defaults() {
+        let config: SearchConfig = serde_json::from_str(r#"{"mode": "optimal", "distance": {"ngram_factor": 0.5}}"#).unwrap();
+        assert_eq!(config.mode, SearchMode::Optimal);
+        assert_eq!(config.max_depth, 20);
+        assert_eq!(config.distance.ngram_factor, 0.5);
+        assert_eq!(config.distance.shorter_penalty, 1.5);
+    }
+    
+    #[test]
+    fn limits_end_the_search_with_a_reason() {
+        let hints = crate::hint::parse_hints(include_str!("../fixtures/hints.json")).unwrap();
+        let run = |limits: SearchLimits| {
+            let config = SearchConfig { mode: SearchMode::Bidirectional, max_depth: 8, limits, ..SearchConfig::default() };
+            Pathfinder::with_config("あかいとり", "ぬぬ", hints.clone(), config).unwrap().run()
+        };
+        
+        let explored = run(SearchLimits { max_states_explored: Some(50), ..SearchLimits::default() });
+        assert_eq!(explored.terminated_reason, TerminatedReason::StateLimit);
+        assert_eq!(explored.total_states_explored, 50);
+        assert!(!explored.best_attempts.is_empty());
+        
+        let stopped = run(SearchLimits { max_states_stored: Some(500), on_memory_limit: LimitAction::Stop, ..SearchLimits::default() });
+        assert_eq!(stopped.terminated_reason, TerminatedReason::MemoryLimit);
+        assert_eq!(stopped.states_pruned, 0);
+        
+        // Pruning keeps the search going past the point where stopping ends it
+        let pruned = run(SearchLimits { max_states_stored: Some(500), ..SearchLimits::default() });
+        assert_eq!(pruned.terminated_reason, TerminatedReason::MemoryLimit);
+        assert!(pruned.states_pruned > 0);
+        assert!(pruned.total_states_explored > stopped.total_states_explored);
+    }
+}
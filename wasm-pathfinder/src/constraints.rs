@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+
+use crate::error::Error;
+use crate::hint::Hint;
+
+// Limits on which hints a path may use, by hint name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HintConstraints {
+    // Times any one hint may be applied; None for no limit
+    pub max_uses: Option<u8>,
+    // Per-hint limits, overriding `max_uses`
+    pub max_uses_by_hint: BTreeMap<String, u8>,
+    // Hints that must appear in the path
+    pub required: Vec<String>,
+    // Whether `required` hints must appear in the listed order
+    pub required_in_order: bool,
+    // Hints that may not be used at all
+    pub banned: Vec<String>,
+}
+
+// Per-state record of how a path used the constrained hints: a use count
+// for every limited hint, then the progress through the required hints.
+// None when there are no limits or requirements to track.
+pub(crate) type Usage = Option<Box<[u8]>>;
+
+// Constraints resolved against the hint list
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UsageRules {
+    banned: Vec<bool>,
+    // Count slot and limit of each hint that has one
+    limits: Vec<Option<(usize, u8)>>,
+    slots: usize,
+    // Hint name of every required entry
+    required: Vec<String>,
+    required_in_order: bool,
+    names: Vec<String>,
+}
+
+impl UsageRules {
+    pub(crate) fn new(constraints: &HintConstraints, hints: &[Hint]) -> Result<UsageRules, Error> {
+        let names: Vec<String> = hints.iter().map(|hint| hint.name.clone()).collect();
+        let referenced = constraints.max_uses_by_hint.keys()
+            .chain(&constraints.required)
+            .chain(&constraints.banned);
+        for name in referenced {
+            if !names.contains(name) {
+                return Err(Error::InvalidConstraints(format!("Unknown hint in constraints: \"{}\"", name)));
+            }
+        }
+        if let Some(name) = constraints.required.iter().find(|name| constraints.banned.contains(name)) {
+            return Err(Error::InvalidConstraints(format!("Hint \"{}\" is both required and banned", name)));
+        }
+        
+        // Hints sharing a name share a count
+        let mut slot_names: Vec<&str> = Vec::new();
+        let limits = names.iter()
+            .map(|name| {
+                let limit = constraints.max_uses_by_hint.get(name).copied().or(constraints.max_uses)?;
+                let slot = match slot_names.iter().position(|slot_name| slot_name == name) {
+                    Some(slot) => slot,
+                    None => {
+                        slot_names.push(name);
+                        slot_names.len() - 1
+                    }
+                };
+                Some((slot, limit))
+            })
+            .collect();
+        
+        Ok(UsageRules {
+            banned: names.iter().map(|name| constraints.banned.contains(name)).collect(),
+            limits,
+            slots: slot_names.len(),
+            required: constraints.required.clone(),
+            required_in_order: constraints.required_in_order,
+            names,
+        })
+    }
+    
+    fn progress_len(&self) -> usize {
+        match (self.required.is_empty(), self.required_in_order) {
+            (true, _) => 0,
+            (false, true) => 1,
+            (false, false) => self.required.len(),
+        }
+    }
+    
+    pub(crate) fn initial(&self) -> Usage {
+        let len = self.slots + self.progress_len();
+        (len > 0).then(|| vec![0; len].into_boxed_slice())
+    }
+    
+    pub(crate) fn is_banned(&self, hint_index: usize) -> bool {
+        self.banned.get(hint_index).copied().unwrap_or(false)
+    }
+    
+    // Usage after applying the hint, or None if that goes over its limit
+    pub(crate) fn advance(&self, usage: &Usage, hint_index: usize) -> Option<Usage> {
+        if self.is_banned(hint_index) {
+            return None;
+        }
+        let Some(counts) = usage else {
+            return Some(None);
+        };
+        let mut counts = counts.clone();
+        
+        if let Some(&Some((slot, limit))) = self.limits.get(hint_index) {
+            if counts[slot] >= limit {
+                return None;
+            }
+            counts[slot] += 1;
+        }
+        
+        let name = &self.names[hint_index];
+        let progress = &mut counts[self.slots..];
+        if self.required_in_order {
+            // The required hints only need to form a subsequence of the path
+            if let Some(next) = progress.first_mut() {
+                if self.required.get(*next as usize) == Some(name) {
+                    *next += 1;
+                }
+            }
+        } else if let Some(item) = (0..self.required.len()).find(|&i| progress[i] == 0 && self.required[i] == *name) {
+            progress[item] = 1;
+        }
+        
+        Some(Some(counts))
+    }
+    
+    // Required hints the path still has to use
+    pub(crate) fn remaining_required(&self, usage: &Usage) -> usize {
+        let Some(counts) = usage else {
+            return 0;
+        };
+        let progress = &counts[self.slots..];
+        if self.required_in_order {
+            progress.first().map_or(0, |&next| self.required.len() - next as usize)
+        } else {
+            progress.iter().filter(|&&done| done == 0).count()
+        }
+    }
+    
+    // Whether a whole path of hint indices keeps to the constraints
+    pub(crate) fn allows_path(&self, hint_path: &[usize]) -> bool {
+        let mut usage = self.initial();
+        for &hint_index in hint_path {
+            match self.advance(&usage, hint_index) {
+                Some(next) => usage = next,
+                None => return false,
+            }
+        }
+        self.remaining_required(&usage) == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Pathfinder, SearchMode};
+    use crate::hint::parse_hints;
+    
+    const HINTS: &str = include_str!("../fixtures/hints.json");
+    
+    fn path_with(constraints: HintConstraints, mode: SearchMode) -> Option<Vec<String>> {
+        let hints = parse_hints(HINTS).unwrap();
+        let mut engine = Pathfinder::new("あかいとり", "かと", hints, 6, mode).unwrap();
+        engine.set_constraints(constraints).unwrap();
+        let result = engine.run();
+        result.found.then_some(result.path)
+    }
+    
+    #[test]
+    fn required_hints_keep_their_order() {
+        let constraints = HintConstraints {
+            required: vec!["贖い".to_string(), "リトル".to_string(), "鳥居".to_string()],
+            required_in_order: true,
+            ..HintConstraints::default()
+        };
+        for mode in [SearchMode::Heuristic, SearchMode::Bidirectional, SearchMode::Optimal] {
+            assert_eq!(path_with(constraints.clone(), mode).unwrap(), ["贖い", "リトル", "鳥居"]);
+        }
+    }
+    
+    #[test]
+    fn banned_hints_are_never_used() {
+        let constraints = HintConstraints { banned: vec!["鳥居".to_string()], ..HintConstraints::default() };
+        assert!(path_with(constraints, SearchMode::Optimal).is_none());
+    }
+    
+    #[test]
+    fn rejects_unknown_and_contradictory_names() {
+        let hints = parse_hints(HINTS).unwrap();
+        let unknown = HintConstraints { banned: vec!["nope".to_string()], ..HintConstraints::default() };
+        assert!(UsageRules::new(&unknown, &hints).is_err());
+        let both = HintConstraints {
+            required: vec!["鳥居".to_string()],
+            banned: vec!["鳥居".to_string()],
+            ..HintConstraints::default()
+        };
+        assert!(UsageRules::new(&both, &hints).is_err());
+    }
+}
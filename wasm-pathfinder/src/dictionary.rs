@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+use fst::{Set, Streamer};
+use serde::{Serialize, Deserialize};
+
+use crate::error::Error;
+use crate::kana::Normalization;
+
+// What to do with intermediate texts that are not made of dictionary words
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DictionaryMode {
+    // Never queue them
+    Require,
+    // Queue them behind texts that are
+    Prefer,
+}
+
+impl std::str::FromStr for DictionaryMode {
+    type Err = String;
+    
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "require" => Ok(DictionaryMode::Require),
+            "prefer" => Ok(DictionaryMode::Prefer),
+            _ => Err(format!("Unknown dictionary mode: {}", mode)),
+        }
+    }
+}
+
+enum Words {
+    List(HashSet<String>),
+    Fst(Set<Vec<u8>>),
+}
+
+// Stored form of a dictionary, for engine snapshots
+#[derive(Serialize, Deserialize)]
+pub(crate) enum DictionaryData {
+    List(Vec<String>),
+    Fst(Vec<u8>),
+}
+
+// Word list used to judge whether intermediate texts are real words
+pub struct Dictionary {
+    words: Words,
+    max_word_chars: usize,
+}
+
+impl Dictionary {
+    // One word per line; blank lines and lines starting with `#` are skipped.
+    // Words are normalized like the engine's default, so katakana entries
+    // match hiragana texts.
+    pub fn from_word_list(text: &str) -> Dictionary {
+        let normalization = Normalization::default();
+        let words: HashSet<String> = text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|word| normalization.apply(word))
+            .collect();
+        Dictionary::from_words(words)
+    }
+    
+    // An `fst::Set` built from normalized (hiragana, NFC) words
+    pub fn from_fst(bytes: Vec<u8>) -> Result<Dictionary, Error> {
+        let set = Set::new(bytes)
+            .map_err(|e| Error::InvalidDictionary(format!("Failed to read FST dictionary: {}", e)))?;
+        
+        let mut max_word_chars = 0;
+        let mut stream = set.stream();
+        while let Some(key) = stream.next() {
+            let chars = String::from_utf8_lossy(key).chars().count();
+            max_word_chars = max_word_chars.max(chars);
+        }
+        
+        Ok(Dictionary { words: Words::Fst(set), max_word_chars })
+    }
+    
+    fn from_words(words: HashSet<String>) -> Dictionary {
+        let max_word_chars = words.iter().map(|word| word.chars().count()).max().unwrap_or(0);
+        Dictionary { words: Words::List(words), max_word_chars }
+    }
+    
+    pub fn contains(&self, word: &str) -> bool {
+        match &self.words {
+            Words::List(words) => words.contains(word),
+            Words::Fst(set) => set.contains(word),
+        }
+    }
+    
+    // Whether the text is a word or can be split into a sequence of words
+    pub fn accepts(&self, text: &str) -> bool {
+        if text.is_empty() || self.contains(text) {
+            return true;
+        }
+        
+        // reachable[i]: the first i characters split into words
+        let boundaries: Vec<usize> = text.char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(text.len()))
+            .collect();
+        let mut reachable = vec![false; boundaries.len()];
+        reachable[0] = true;
+        for end in 1..boundaries.len() {
+            let earliest = end.saturating_sub(self.max_word_chars);
+            reachable[end] = (earliest..end).any(|start| {
+                reachable[start] && self.contains(&text[boundaries[start]..boundaries[end]])
+            });
+        }
+        reachable[boundaries.len() - 1]
+    }
+    
+    pub(crate) fn to_data(&self) -> DictionaryData {
+        match &self.words {
+            Words::List(words) => DictionaryData::List(words.iter().cloned().collect()),
+            Words::Fst(set) => DictionaryData::Fst(set.as_fst().as_bytes().to_vec()),
+        }
+    }
+    
+    pub(crate) fn from_data(data: DictionaryData) -> Result<Dictionary, Error> {
+        match data {
+            DictionaryData::List(words) => Ok(Dictionary::from_words(words.into_iter().collect())),
+            DictionaryData::Fst(bytes) => Dictionary::from_fst(bytes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Pathfinder, SearchMode};
+    use crate::hint::{Hint, HintOperation};
+    
+    #[test]
+    fn accepts_texts_made_of_words() {
+        let dictionary = Dictionary::from_word_list("# kana\nナゾ\n\n  とき  \n");
+        assert!(dictionary.contains("なぞ"));
+        assert!(!dictionary.contains("なぞとき"));
+        assert!(dictionary.accepts("なぞとき"));
+        assert!(dictionary.accepts("ときなぞとき"));
+        assert!(dictionary.accepts(""));
+        assert!(!dictionary.accepts("なぞと"));
+        assert!(!dictionary.accepts("# kana"));
+    }
+    
+    #[test]
+    fn loads_fst_sets() {
+        let bytes = Set::from_iter(["とき", "なぞ"]).unwrap().as_fst().as_bytes().to_vec();
+        let dictionary = Dictionary::from_fst(bytes).unwrap();
+        assert!(dictionary.contains("とき"));
+        assert!(dictionary.accepts("なぞとき"));
+        assert!(!dictionary.accepts("なぞと"));
+        assert!(Dictionary::from_fst(b"not an fst".to_vec()).is_err());
+    }
+    
+    #[test]
+    fn require_drops_texts_that_prefer_only_ranks_lower() {
+        let search = |words: &str, mode: DictionaryMode| {
+            let hints = vec![Hint::new("a", HintOperation::remove("あ")), Hint::new("i", HintOperation::remove("い"))];
+            let mut engine = Pathfinder::new("あいう", "う", hints, 3, SearchMode::Heuristic).unwrap();
+            engine.set_dictionary(Dictionary::from_word_list(words), mode);
+            engine.run()
+        };
+        
+        // Only the route through the word いう is left
+        let result = search("いう", DictionaryMode::Require);
+        assert_eq!(result.path, vec!["a", "i"]);
+        
+        // Neither いう nor あう is a word
+        assert!(!search("え", DictionaryMode::Require).found);
+        assert!(search("え", DictionaryMode::Prefer).found);
+    }
+}
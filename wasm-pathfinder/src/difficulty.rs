@@ -0,0 +1,216 @@
+use serde::{Serialize, Deserialize};
+
+use crate::engine::{Pathfinder, SearchMode, SearchStep};
+use crate::error::Error;
+use crate::hint::Hint;
+
+// States the solver may explore before a puzzle counts as maximally hard
+const SOLVE_BUDGET: usize = 200_000;
+
+// Share of the score taken by each part of the breakdown
+const SEARCH_WEIGHT: f64 = 0.4;
+const GROUP_WEIGHT: f64 = 0.25;
+const DECOY_WEIGHT: f64 = 0.15;
+const BRANCHING_WEIGHT: f64 = 0.2;
+
+// Decoys and branching at which those parts saturate
+const MAX_DECOYS: f64 = 25.0;
+const MAX_BRANCHING: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DifficultyBand {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl DifficultyBand {
+    // Band for a score between 0 and 100
+    pub fn from_score(score: f64) -> DifficultyBand {
+        if score < 25.0 {
+            DifficultyBand::Easy
+        } else if score < 50.0 {
+            DifficultyBand::Medium
+        } else if score < 75.0 {
+            DifficultyBand::Hard
+        } else {
+            DifficultyBand::Expert
+        }
+    }
+}
+
+// Raw measurements and the normalized (0 to 1) part each one contributes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DifficultyBreakdown {
+    pub states_explored: usize,
+    pub solved: bool,
+    // Puzzle hints per group, A to D
+    pub group_counts: [usize; 4],
+    // Hints from the pool, not used by the puzzle, that change the question
+    pub decoy_hints: usize,
+    // Average number of pool hints that apply along the intended path
+    pub branching_factor: f64,
+    pub search_component: f64,
+    pub group_component: f64,
+    pub decoy_component: f64,
+    pub branching_component: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DifficultyReport {
+    // 0 (trivial) to 100 (hardest)
+    pub score: f64,
+    pub band: DifficultyBand,
+    pub breakdown: DifficultyBreakdown,
+}
+
+// Rate how hard it is to get from the question to the answer.
+//
+// `puzzle_hints` are the hints the question was built with, in order, and
+// `hint_pool` is everything the solver can choose from. The search part
+// runs the heuristic engine over the pool, as the solver UI does, and uses
+// the states it needed on a log scale.
+pub fn analyze_difficulty(question: &str, answer: &str, puzzle_hints: &[Hint], hint_pool: &[Hint], max_depth: usize) -> Result<DifficultyReport, Error> {
+    let mut engine = Pathfinder::new(question, answer, hint_pool.to_vec(), max_depth, SearchMode::Heuristic)?;
+    let mut solved = false;
+    let mut states_explored = 0;
+    while !engine.is_complete() && states_explored < SOLVE_BUDGET {
+        match engine.step(1000) {
+            SearchStep::Found(result) => {
+                solved = true;
+                states_explored = result.total_states_explored;
+                break;
+            },
+            SearchStep::Progress(progress) => states_explored = progress.states_explored,
+        }
+    }
+    
+    let mut group_counts = [0; 4];
+    for group in puzzle_hints.iter().filter_map(|hint| hint.group) {
+        group_counts[group.rank()] += 1;
+    }
+    
+    let is_puzzle_hint = |hint: &Hint| {
+        puzzle_hints.iter().any(|p| p.name == hint.name && p.operation == hint.operation)
+    };
+    let decoy_hints = hint_pool.iter()
+        .filter(|hint| !is_puzzle_hint(hint) && hint.apply(question).is_some())
+        .count();
+    
+    // Follow the intended decoding and count the options at every step
+    let mut text = question.to_string();
+    let mut applicable_total = 0;
+    let mut steps = 0;
+    for hint in puzzle_hints {
+        applicable_total += hint_pool.iter().filter(|h| h.apply(&text).is_some()).count();
+        steps += 1;
+        match hint.apply(&text) {
+            Some(new_text) => text = new_text,
+            None => break,
+        }
+    }
+    let branching_factor = if steps > 0 { applicable_total as f64 / steps as f64 } else { 0.0 };
+    
+    let search_component = if solved {
+        (((states_explored + 1) as f64).ln() / ((SOLVE_BUDGET + 1) as f64).ln()).min(1.0)
+    } else {
+        1.0
+    };
+    let grouped: usize = group_counts.iter().sum();
+    let group_component = if grouped > 0 {
+        group_counts.iter().enumerate().map(|(rank, &count)| rank * count).sum::<usize>() as f64 / (3 * grouped) as f64
+    } else {
+        0.0
+    };
+    let decoy_component = (decoy_hints as f64 / MAX_DECOYS).min(1.0);
+    let branching_component = ((branching_factor - 1.0) / (MAX_BRANCHING - 1.0)).clamp(0.0, 1.0);
+    
+    let score = 100.0 * (SEARCH_WEIGHT * search_component
+        + GROUP_WEIGHT * group_component
+        + DECOY_WEIGHT * decoy_component
+        + BRANCHING_WEIGHT * branching_component);
+    
+    Ok(DifficultyReport {
+        score,
+        band: DifficultyBand::from_score(score),
+        breakdown: DifficultyBreakdown {
+            states_explored,
+            solved,
+            group_counts,
+            decoy_hints,
+            branching_factor,
+            search_component,
+            group_component,
+            decoy_component,
+            branching_component,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hint::{HintGroup, HintOperation};
+    
+    fn grouped(name: &str, operation: HintOperation, group: HintGroup) -> Hint {
+        Hint { group: Some(group), ..Hint::new(name, operation) }
+    }
+    
+    // かあい → あい → あ, with one pool hint that changes the question and one
+    // that never applies
+    fn puzzle() -> (Vec<Hint>, Vec<Hint>) {
+        let puzzle_hints = vec![
+            grouped("-か", HintOperation::remove("か"), HintGroup::A),
+            grouped("-い", HintOperation::remove("い"), HintGroup::C),
+        ];
+        let mut hint_pool = puzzle_hints.clone();
+        hint_pool.push(grouped("あう", HintOperation::replace("あ", "う"), HintGroup::B));
+        hint_pool.push(grouped("-え", HintOperation::remove("え"), HintGroup::B));
+        (puzzle_hints, hint_pool)
+    }
+    
+    #[test]
+    fn counts_groups_decoys_and_branching() {
+        let (puzzle_hints, hint_pool) = puzzle();
+        let breakdown = analyze_difficulty("かあい", "あ", &puzzle_hints, &hint_pool, 4).unwrap().breakdown;
+        assert!(breakdown.solved);
+        assert_eq!(breakdown.group_counts, [1, 0, 1, 0]);
+        assert!((breakdown.group_component - 1.0 / 3.0).abs() < 1e-9);
+        // -え leaves the question as it is
+        assert_eq!(breakdown.decoy_hints, 1);
+        // Three hints apply to かあい, then two to あい
+        assert_eq!(breakdown.branching_factor, 2.5);
+    }
+    
+    #[test]
+    fn pool_copies_of_puzzle_hints_are_not_decoys() {
+        let (puzzle_hints, mut hint_pool) = puzzle();
+        hint_pool.push(puzzle_hints[0].clone());
+        // Same name but a different operation is a different hint
+        hint_pool.push(Hint::new("-か", HintOperation::remove("あ")));
+        let breakdown = analyze_difficulty("かあい", "あ", &puzzle_hints, &hint_pool, 4).unwrap().breakdown;
+        assert_eq!(breakdown.decoy_hints, 2);
+    }
+    
+    #[test]
+    fn unsolvable_puzzles_max_out_the_search() {
+        let (puzzle_hints, hint_pool) = puzzle();
+        let breakdown = analyze_difficulty("かあい", "お", &puzzle_hints, &hint_pool, 4).unwrap().breakdown;
+        assert!(!breakdown.solved);
+        assert_eq!(breakdown.search_component, 1.0);
+    }
+    
+    #[test]
+    fn bands_start_at_their_lower_boundary() {
+        assert_eq!(DifficultyBand::from_score(0.0), DifficultyBand::Easy);
+        assert_eq!(DifficultyBand::from_score(24.9), DifficultyBand::Easy);
+        assert_eq!(DifficultyBand::from_score(25.0), DifficultyBand::Medium);
+        assert_eq!(DifficultyBand::from_score(49.9), DifficultyBand::Medium);
+        assert_eq!(DifficultyBand::from_score(50.0), DifficultyBand::Hard);
+        assert_eq!(DifficultyBand::from_score(74.9), DifficultyBand::Hard);
+        assert_eq!(DifficultyBand::from_score(75.0), DifficultyBand::Expert);
+        assert_eq!(DifficultyBand::from_score(100.0), DifficultyBand::Expert);
+    }
+}
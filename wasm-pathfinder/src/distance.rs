@@ -0,0 +1,143 @@
+use std::cmp::min;
+use std::collections::HashSet;
+use serde::{Serialize, Deserialize};
+
+// Tunables of `weighted_distance`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DistanceWeights {
+    // Largest share of the edit distance that shared n-grams can take off
+    pub ngram_factor: f64,
+    // Per-character penalty when the text is longer than the target
+    pub longer_penalty: f64,
+    // Per-character penalty when the text is shorter than the target
+    pub shorter_penalty: f64,
+}
+
+impl Default for DistanceWeights {
+    fn default() -> Self {
+        DistanceWeights {
+            ngram_factor: 0.4,
+            longer_penalty: 0.2,
+            shorter_penalty: 1.5,
+        }
+    }
+}
+
+// Weighted Levenshtein distance from a search text to the target. Extra
+// characters are cheap because hints mostly remove text, missing ones are
+// expensive, and shared 2-grams and 3-grams pull the distance down.
+pub fn weighted_distance(s1: &str, s2: &str) -> f64 {
+    weighted_distance_with(s1, s2, &DistanceWeights::default())
+}
+
+// `weighted_distance` with custom weights
+pub fn weighted_distance_with(s1: &str, s2: &str, weights: &DistanceWeights) -> f64 {
+    // Calculate Levenshtein distance
+    let chars1: Vec<char> = s1.chars().collect();
+    let chars2: Vec<char> = s2.chars().collect();
+    let len1 = chars1.len();
+    let len2 = chars2.len();
+    
+    if len1 == 0 {
+        return len2 as f64 * 2.0; // Heavy penalty for empty string
+    }
+    if len2 == 0 {
+        return len1 as f64;
+    }
+    
+    let base_distance = levenshtein(&chars1, &chars2) as f64;
+    
+    // N-gram matching bonus
+    let ngram_bonus = calculate_ngram_bonus(&chars1, &chars2, base_distance, weights.ngram_factor);
+    
+    // Length difference penalty
+    let length_penalty = if len1 > len2 {
+        // Current is longer than target - light penalty
+        // Easier to remove characters
+        ((len1 - len2) as f64) * weights.longer_penalty
+    } else if len1 < len2 {
+        // Current is shorter than target - heavy penalty
+        // Harder to add back characters
+        ((len2 - len1) as f64) * weights.shorter_penalty
+    } else {
+        0.0
+    };
+    
+    // Final weighted distance
+    let weighted_distance = base_distance - ngram_bonus + length_penalty;
+    
+    // Ensure distance is non-negative
+    weighted_distance.max(0.0)
+}
+
+// Unit-cost edit distance, keeping only two rows of the table
+pub(crate) fn levenshtein(chars1: &[char], chars2: &[char]) -> u32 {
+    let mut prev_row: Vec<u32> = (0..=chars2.len() as u32).collect();
+    let mut curr_row = vec![0u32; chars2.len() + 1];
+    
+    for i in 1..=chars1.len() {
+        curr_row[0] = i as u32;
+        
+        for j in 1..=chars2.len() {
+            let cost = if chars1[i - 1] == chars2[j - 1] { 0 } else { 1 };
+            curr_row[j] = min(
+                min(prev_row[j] + 1, curr_row[j - 1] + 1),
+                prev_row[j - 1] + cost
+            );
+        }
+        
+        std::mem::swap(&mut prev_row, &mut curr_row);
+    }
+    
+    prev_row[chars2.len()]
+}
+
+fn calculate_ngram_bonus(chars1: &[char], chars2: &[char], base_distance: f64, ngram_factor: f64) -> f64 {
+    let len1 = chars1.len();
+    let len2 = chars2.len();
+    
+    if len1 < 2 || len2 < 2 {
+        return 0.0;
+    }
+    
+    let mut ngram_matches = 0;
+    let mut total_possible_ngrams = 0;
+    
+    // Count 2-gram matches
+    let mut bigrams2 = HashSet::new();
+    for i in 0..len2.saturating_sub(1) {
+        bigrams2.insert((chars2[i], chars2[i + 1]));
+    }
+    total_possible_ngrams += bigrams2.len();
+    
+    for i in 0..len1.saturating_sub(1) {
+        if bigrams2.contains(&(chars1[i], chars1[i + 1])) {
+            ngram_matches += 1;
+        }
+    }
+    
+    // Count 3-gram matches (weighted more heavily)
+    if len1 >= 3 && len2 >= 3 {
+        let mut trigrams2 = HashSet::new();
+        for i in 0..len2.saturating_sub(2) {
+            trigrams2.insert((chars2[i], chars2[i + 1], chars2[i + 2]));
+        }
+        total_possible_ngrams += trigrams2.len() * 2; // Weight 3-grams more
+        
+        for i in 0..len1.saturating_sub(2) {
+            if trigrams2.contains(&(chars1[i], chars1[i + 1], chars1[i + 2])) {
+                ngram_matches += 2; // 3-gram matches count double
+            }
+        }
+    }
+    
+    // Calculate bonus based on n-gram matches
+    if total_possible_ngrams > 0 {
+        let ngram_ratio = ngram_matches as f64 / total_possible_ngrams as f64;
+        // N-gram bonus can reduce distance by up to `ngram_factor` (40% by default)
+        ngram_ratio * base_distance * ngram_factor
+    } else {
+        0.0
+    }
+}
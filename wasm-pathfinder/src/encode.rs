@@ -0,0 +1,133 @@
+use rand::Rng;
+
+use crate::error::Error;
+use crate::hint::{Hint, OccurrenceMode};
+
+// Random placements to try before giving up on a hint list
+const MAX_ATTEMPTS: usize = 32;
+
+// Turn an answer into a question that the hints decode back, in order.
+//
+// Hints are undone in reverse: a removed target is inserted at 1 to 3 random
+// positions and a replacement is turned back into its target, like `encode`
+// in cipher.ts; a hint that rewrites a single occurrence is undone at one
+// random place only. Every candidate is decoded with `Hint::apply`; if some hint
+// would not apply cleanly (for example because the answer already contains
+// the removed text) another placement is tried before reporting an error.
+pub fn encode<R: Rng>(answer: &str, hints: &[Hint], rng: &mut R) -> Result<String, Error> {
+    if hints.is_empty() {
+        return Err(Error::Encoding("No hints given".to_string()));
+    }
+    
+    for _ in 0..MAX_ATTEMPTS {
+        let question = hints.iter().rev()
+            .fold(answer.to_string(), |text, hint| encode_hint(&text, hint, rng));
+        if decode(&question, hints).as_deref() == Some(answer) {
+            return Ok(question);
+        }
+    }
+    
+    Err(Error::Encoding(format!(
+        "Hints [{}] do not decode back to \"{}\"",
+        hints.iter().map(|h| h.name.as_str()).collect::<Vec<_>>().join(", "),
+        answer
+    )))
+}
+
+// Apply the hints in order, failing if any of them changes nothing. A hint
+// that rewrites a single occurrence must give the same text whichever
+// occurrence it picks, so the question has one answer.
+pub fn decode(question: &str, hints: &[Hint]) -> Option<String> {
+    hints.iter().try_fold(question.to_string(), |text, hint| {
+        let mut texts = hint.applications(&text);
+        if texts.len() == 1 { texts.pop() } else { None }
+    })
+}
+
+// Undo a single hint
+fn encode_hint<R: Rng>(text: &str, hint: &Hint, rng: &mut R) -> String {
+    let target = hint.operation.target();
+    let replacement = hint.operation.replacement();
+    let single = hint.occurrences == Some(OccurrenceMode::Single);
+    
+    if !replacement.is_empty() {
+        if !single {
+            return text.replace(&*replacement, target);
+        }
+        let starts: Vec<usize> = text.match_indices(&*replacement).map(|(i, _)| i).collect();
+        if starts.is_empty() {
+            return text.to_string();
+        }
+        let start = starts[rng.random_range(0..starts.len())];
+        return format!("{}{}{}", &text[..start], target, &text[start + replacement.len()..]);
+    }
+    
+    // Pick 1 to 3 distinct gaps between characters (0..=len), or a single
+    // one for a hint that removes one occurrence
+    let mut chars: Vec<String> = text.chars().map(String::from).collect();
+    let insert_count = if single { 1 } else { rng.random_range(1..=3).min(chars.len() + 1) };
+    let mut positions = Vec::with_capacity(insert_count);
+    while positions.len() < insert_count {
+        let position = rng.random_range(0..=chars.len());
+        if !positions.contains(&position) {
+            positions.push(position);
+        }
+    }
+    
+    // Insert from the back so earlier positions stay valid
+    positions.sort_unstable_by(|a, b| b.cmp(a));
+    for position in positions {
+        chars.insert(position, target.to_string());
+    }
+    chars.concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+    use crate::hint::parse_hints;
+    
+    const HINTS: &str = r#"[
+        {"name": "狸", "reading": "たぬき", "operation": {"type": "remove", "target": "た"}, "description": ""},
+        {"name": "こけし", "reading": "こけし", "operation": {"type": "remove", "target": "こ"}, "description": ""},
+        {"name": "鏡", "reading": "かがみ", "operation": {"type": "replace", "target": "か", "replacement": "み"}, "description": ""}
+    ]"#;
+    
+    #[test]
+    fn same_seed_gives_same_question() {
+        let hints = parse_hints(HINTS).unwrap();
+        let first = encode("なぞみとき", &hints, &mut SmallRng::seed_from_u64(7)).unwrap();
+        let second = encode("なぞみとき", &hints, &mut SmallRng::seed_from_u64(7)).unwrap();
+        
+        assert_eq!(first, second);
+        assert_eq!(decode(&first, &hints).as_deref(), Some("なぞみとき"));
+    }
+    
+    #[test]
+    fn single_occurrence_hints_are_undone_once() {
+        let mut hints = parse_hints(HINTS).unwrap();
+        for hint in &mut hints {
+            hint.occurrences = Some(OccurrenceMode::Single);
+        }
+        let question = encode("なぞみとき", &hints, &mut SmallRng::seed_from_u64(7)).unwrap();
+        assert_eq!(question.chars().count(), "なぞみとき".chars().count() + 2);
+        assert_eq!(decode(&question, &hints).as_deref(), Some("なぞみとき"));
+        
+        // Removing either こ gives a different text, so there is no one answer
+        assert_eq!(decode("こあこ", &hints[1..2]), None);
+    }
+    
+    #[test]
+    fn rejects_hints_that_cannot_decode_back() {
+        let hints = parse_hints(HINTS).unwrap();
+        
+        // Removing "た" would also remove the answer's own "た"
+        let error = encode("たぬき", &hints[..1], &mut SmallRng::seed_from_u64(7)).unwrap_err();
+        assert!(matches!(error, Error::Encoding(_)));
+        
+        // There is no "み" to turn back into "か"
+        assert!(encode("なぞとき", &hints[2..], &mut SmallRng::seed_from_u64(7)).is_err());
+    }
+}
@@ -0,0 +1,1438 @@
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use std::cmp::Ordering;
+use serde::{Serialize, Deserialize};
+
+use crate::bounds::HintBounds;
+use crate::cache::{CacheStats, ClockCache};
+use crate::constraints::{HintConstraints, Usage, UsageRules};
+use crate::dictionary::{Dictionary, DictionaryMode};
+use crate::config::{LimitAction, SearchConfig};
+use crate::error::Error;
+use crate::heuristic::Heuristic;
+use crate::interner::{Interner, TextId};
+use crate::matcher::HintMatcher;
+use crate::hint::{parse_hints, validate_hints, Hint, OccurrenceMode};
+use crate::kana::Normalization;
+use crate::progress::{calculate_progress_percentage, estimate_search_space};
+
+pub use partition::{merge_results, Partition, StateBatch};
+use partition::TransferredState;
+
+mod partition;
+mod snapshot;
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+mod parallel;
+
+// Search strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    // Greedy best-first search on the weighted distance
+    Heuristic,
+    // Heuristic search from both ends, meeting in the middle
+    Bidirectional,
+    // A* on an admissible lower bound, returning a cheapest path
+    Optimal,
+}
+
+impl std::str::FromStr for SearchMode {
+    type Err = String;
+    
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "heuristic" => Ok(SearchMode::Heuristic),
+            "bidirectional" => Ok(SearchMode::Bidirectional),
+            "optimal" => Ok(SearchMode::Optimal),
+            _ => Err(format!("Unknown search mode: {}", mode)),
+        }
+    }
+}
+
+// Index of a node in the search arena
+type NodeId = u32;
+
+// States are told apart by their text and by how they used constrained hints
+type StateKey = (TextId, Usage);
+
+// Heuristic cost added to texts outside the dictionary in prefer mode. Optimal
+// search leaves it out so that its paths stay cheapest.
+const DICTIONARY_PENALTY: f64 = 0.5;
+
+// Share of `max_states_stored` to prune down to, so pruning runs in batches
+const PRUNE_TARGET: f64 = 0.75;
+
+// Placeholder move stored on root nodes
+const ROOT_MOVE: Move = Move { hint: 0, position: None };
+
+// A text one hint away from an expanded state, with the hint usage it leads
+// to. The distance is filled in when successors are computed in parallel.
+struct Successor {
+    hint: usize,
+    mv: Move,
+    text: String,
+    usage: Usage,
+    distance: Option<f64>,
+}
+
+// One hint application; `position` is the character where the rewritten
+// occurrence starts, or None when every occurrence was rewritten
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Move {
+    hint: usize,
+    position: Option<usize>,
+}
+
+// Arena entry for a reached state. Paths are rebuilt by following parent
+// links, so queued states stay small no matter how deep the search goes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct SearchNode {
+    parent: Option<NodeId>,
+    hint: u32,
+    position: Option<u32>,
+}
+
+// Search state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SearchState {
+    text: TextId,
+    node: NodeId,
+    depth: usize,
+    // Summed cost of the hints applied so far
+    cost: f64,
+    usage: Usage,
+    distance: f64,
+    heuristic_score: f64,
+}
+
+impl Ord for SearchState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reverse for min-heap behavior, preferring deeper states on ties
+        other.heuristic_score.partial_cmp(&self.heuristic_score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.depth.cmp(&other.depth))
+    }
+}
+
+impl PartialOrd for SearchState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SearchState {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SearchState {}
+
+// Best attempt tracking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BestAttempt {
+    pub text: String,
+    pub path: Vec<String>,
+    pub distance: f64,
+}
+
+// Best attempt as tracked during the search, with its text interned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TrackedAttempt {
+    text: TextId,
+    path: Vec<String>,
+    distance: f64,
+}
+
+// Why a search ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminatedReason {
+    Found,
+    // Every reachable state within the depth limit was explored
+    Exhausted,
+    // `max_states_explored` was reached
+    StateLimit,
+    // `max_states_stored` was reached and pruning could not make room, or the
+    // frontier ran out after pruning dropped states
+    MemoryLimit,
+}
+
+// Search result
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub found: bool,
+    pub path: Vec<String>,
+    pub steps: Vec<String>,
+    // Occurrence each step rewrote, by character position; None for all of them
+    pub occurrences: Vec<Option<usize>>,
+    // Summed cost of the hints on the path
+    pub total_cost: f64,
+    pub best_attempts: Vec<BestAttempt>,
+    pub total_states_explored: usize,
+    pub proven_optimal: bool,
+    pub terminated_reason: TerminatedReason,
+    // Frontier states dropped to stay under the memory limit
+    pub states_pruned: usize,
+}
+
+// Progress update
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProgressUpdate {
+    pub states_explored: usize,
+    pub current_best_distance: f64,
+    pub current_best_text: String,
+    pub queue_size: usize,
+    pub progress_percentage: f64,
+    pub estimated_total_states: usize,
+    pub depth_progress: f64,
+    pub max_depth_reached: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CacheReport {
+    pub distance: CacheStats,
+    pub decode: CacheStats,
+}
+
+// Outcome of a batch of iterations
+#[derive(Debug)]
+pub enum SearchStep {
+    Found(SearchResult),
+    Progress(ProgressUpdate),
+}
+
+// Best-first search over hint applications
+pub struct Pathfinder {
+    // Core data structures
+    queue: BinaryHeap<SearchState>,
+    visited: HashSet<StateKey>,
+    nodes: Vec<SearchNode>,
+    hints: Vec<Hint>,
+    // Every text the search has reached, referred to by id everywhere else
+    interner: Interner,
+    
+    // Search parameters, with start and target normalized
+    start: String,
+    target: String,
+    start_id: TextId,
+    target_id: TextId,
+    config: SearchConfig,
+    // What was typed for each character of `start`, shown in result steps
+    start_surface: Vec<String>,
+    occurrences: OccurrenceMode,
+    constraints: HintConstraints,
+    usage_rules: UsageRules,
+    
+    // Tracking
+    best_attempts: Vec<TrackedAttempt>,
+    best_distance: f64,
+    states_explored: usize,
+    estimated_total_states: usize,
+    max_depth_reached: usize,
+    states_pruned: usize,
+    // Set when a limit ended the search early
+    terminated: Option<TerminatedReason>,
+    
+    // Distance estimate, usually built from `config.heuristic`, with its cache
+    heuristic: Box<dyn Heuristic>,
+    distance_cache: ClockCache<(TextId, TextId), f64>,
+    decode_cache: ClockCache<(TextId, usize), Option<String>>,
+    
+    // Bidirectional search; both maps point to the node and depth of each text
+    forward_paths: HashMap<TextId, (NodeId, usize)>,
+    backward_queue: BinaryHeap<SearchState>,
+    backward_visited: HashMap<TextId, (NodeId, usize)>,
+    
+    // Optimal search; the lower bound counts hints, each costing at least
+    // `min_hint_cost`. Each state maps to the cost and depth of the route that
+    // last got to queue it.
+    best_cost: HashMap<StateKey, (f64, usize)>,
+    hint_bounds: HintBounds,
+    matcher: HintMatcher,
+    min_hint_cost: f64,
+    
+    // Word list intermediate texts are checked against
+    dictionary: Option<(Dictionary, DictionaryMode)>,
+    
+    // Share of the search this engine owns when several engines split it,
+    // and the states waiting to be sent to the other partitions
+    partition: Option<Partition>,
+    outgoing: BTreeMap<u32, Vec<TransferredState>>,
+}
+
+impl Pathfinder {
+    pub fn new(start: &str, target: &str, hints: Vec<Hint>, max_depth: usize, mode: SearchMode) -> Result<Pathfinder, Error> {
+        Self::with_normalization(start, target, hints, max_depth, mode, Normalization::default())
+    }
+    
+    // Create an engine that normalizes the start, target and hint texts first,
+    // so katakana, half-width or decomposed input still matches the hints
+    pub fn with_normalization(start: &str, target: &str, hints: Vec<Hint>, max_depth: usize, mode: SearchMode, normalization: Normalization) -> Result<Pathfinder, Error> {
+        let config = SearchConfig { mode, max_depth, normalization, ..SearchConfig::default() };
+        Self::with_config(start, target, hints, config)
+    }
+    
+    // Create an engine with every tunable taken from `config`
+    pub fn with_config(start: &str, target: &str, hints: Vec<Hint>, config: SearchConfig) -> Result<Pathfinder, Error> {
+        let heuristic = config.heuristic.build(config.distance);
+        Self::with_heuristic(start, target, hints, config, heuristic)
+    }
+    
+    // Create an engine that orders states with the given heuristic instead of
+    // the one `config.heuristic` names. An engine restored with `from_state`
+    // goes back to the named one.
+    pub fn with_heuristic(start: &str, target: &str, hints: Vec<Hint>, config: SearchConfig, heuristic: Box<dyn Heuristic>) -> Result<Pathfinder, Error> {
+        validate_hints(&hints)?;
+        let normalization = config.normalization;
+        let max_depth = config.max_depth;
+        
+        let hints: Vec<Hint> = hints.into_iter()
+            .map(|mut hint| {
+                hint.operation = hint.operation.map_text(|text| normalization.apply(text));
+                hint
+            })
+            .collect();
+        
+        // Keep the typed form of every normalized character of the start
+        let mut start_surface = Vec::new();
+        let mut normalized_start = String::new();
+        for (normalized, surface) in normalization.clusters(start) {
+            for (i, c) in normalized.chars().enumerate() {
+                start_surface.push(if i == 0 { surface.to_string() } else { String::new() });
+                normalized_start.push(c);
+            }
+        }
+        
+        // Estimate total search space
+        let hints_count = hints.len();
+        let estimated_total = estimate_search_space(hints_count, max_depth);
+        let hint_bounds = HintBounds::new(&hints);
+        let matcher = HintMatcher::new(&hints);
+        let min_hint_cost = min_hint_cost(&hints);
+        let distance_cache = ClockCache::new(config.distance_cache_capacity);
+        let decode_cache = ClockCache::new(config.decode_cache_capacity);
+        let target = normalization.apply(target);
+        let mut interner = Interner::default();
+        let start_id = interner.intern(&normalized_start);
+        let target_id = interner.intern(&target);
+        
+        let mut engine = Pathfinder {
+            queue: BinaryHeap::new(),
+            visited: HashSet::new(),
+            nodes: Vec::new(),
+            hints,
+            interner,
+            start: normalized_start,
+            target,
+            start_id,
+            target_id,
+            config,
+            start_surface,
+            occurrences: OccurrenceMode::All,
+            constraints: HintConstraints::default(),
+            usage_rules: UsageRules::default(),
+            best_attempts: Vec::new(),
+            best_distance: f64::INFINITY,
+            states_explored: 0,
+            estimated_total_states: estimated_total,
+            max_depth_reached: 0,
+            states_pruned: 0,
+            terminated: None,
+            heuristic,
+            distance_cache,
+            decode_cache,
+            forward_paths: HashMap::new(),
+            backward_queue: BinaryHeap::new(),
+            backward_visited: HashMap::new(),
+            best_cost: HashMap::new(),
+            hint_bounds,
+            matcher,
+            min_hint_cost,
+            dictionary: None,
+            partition: None,
+            outgoing: BTreeMap::new(),
+        };
+        
+        // Initialize with start state
+        let initial_distance = engine.calculate_distance(start_id, target_id);
+        let initial_state = SearchState {
+            text: start_id,
+            node: engine.push_node(None, ROOT_MOVE),
+            depth: 0,
+            cost: 0.0,
+            usage: None,
+            distance: initial_distance,
+            heuristic_score: initial_distance,
+        };
+        
+        engine.queue.push(initial_state);
+        engine.visited.insert((start_id, None));
+        
+        // Optimal search orders states by cost plus the lower bound instead
+        if engine.config.mode == SearchMode::Optimal {
+            if let Some(mut state) = engine.queue.pop() {
+                let bound = engine.optimal_bound(&engine.start, &None).unwrap_or(0);
+                state.heuristic_score = bound as f64 * engine.min_hint_cost;
+                engine.queue.push(state);
+            }
+            engine.best_cost.insert((start_id, None), (0.0, 0));
+        }
+        
+        // Seed the backward frontier with the target state
+        if engine.config.mode == SearchMode::Bidirectional {
+            let backward_distance = engine.calculate_distance(target_id, start_id);
+            let target_node = engine.push_node(None, ROOT_MOVE);
+            engine.backward_queue.push(SearchState {
+                text: target_id,
+                node: target_node,
+                depth: 0,
+                cost: 0.0,
+                usage: None,
+                distance: backward_distance,
+                heuristic_score: backward_distance,
+            });
+            engine.backward_visited.insert(target_id, (target_node, 0));
+            engine.forward_paths.insert(start_id, (0, 0));
+        }
+        
+        Ok(engine)
+    }
+    
+    // Parse the hints from JSON and create an engine
+    pub fn from_json(start: &str, target: &str, hints_json: &str, max_depth: usize, mode: SearchMode) -> Result<Pathfinder, Error> {
+        Self::new(start, target, parse_hints(hints_json)?, max_depth, mode)
+    }
+    
+    // Choose whether hints rewrite every occurrence of their target or make one
+    // move per occurrence; hints with their own `occurrences` setting keep it
+    pub fn set_occurrence_mode(&mut self, mode: OccurrenceMode) {
+        self.occurrences = mode;
+    }
+    
+    // Limit how often hints may be used, require some of them and ban others.
+    // Constraints change how states are told apart, so they can only be set
+    // before the search starts.
+    pub fn set_constraints(&mut self, constraints: HintConstraints) -> Result<(), Error> {
+        if self.states_explored > 0 {
+            return Err(Error::InvalidConstraints("Constraints must be set before the search starts".to_string()));
+        }
+        self.usage_rules = UsageRules::new(&constraints, &self.hints)?;
+        self.constraints = constraints;
+        
+        // Re-key the start state with the initial usage
+        let usage = self.usage_rules.initial();
+        let states: Vec<SearchState> = self.queue.drain().collect();
+        for mut state in states {
+            state.usage = usage.clone();
+            if self.config.mode == SearchMode::Optimal {
+                let bound = self.optimal_bound(&self.interner.get(state.text), &usage).unwrap_or(0);
+                state.heuristic_score = bound as f64 * self.min_hint_cost;
+            }
+            self.queue.push(state);
+        }
+        self.visited = self.visited.drain().map(|(text, _)| (text, usage.clone())).collect();
+        self.best_cost = self.best_cost.drain().map(|((text, _), cost)| ((text, usage.clone()), cost)).collect();
+        Ok(())
+    }
+    
+    // Check every intermediate text against a word list, either dropping
+    // texts that are not made of words or only ranking them lower
+    pub fn set_dictionary(&mut self, dictionary: Dictionary, mode: DictionaryMode) {
+        self.dictionary = Some((dictionary, mode));
+    }
+    
+    // Run a batch of iterations, returning the result once the target is reached
+    pub fn step(&mut self, iterations: usize) -> SearchStep {
+        match self.search(iterations) {
+            Some(result) => SearchStep::Found(result),
+            None => SearchStep::Progress(self.progress()),
+        }
+    }
+    
+    // Search until the target is found or the search space is exhausted
+    pub fn run(&mut self) -> SearchResult {
+        while !self.is_complete() {
+            if let Some(result) = self.search(1000) {
+                return result;
+            }
+        }
+        self.result()
+    }
+    
+    // Check if search is complete
+    pub fn is_complete(&self) -> bool {
+        self.terminated.is_some() || (self.queue.is_empty() && self.backward_queue.is_empty())
+    }
+    
+    // Result for a search that has not found the target
+    pub fn result(&self) -> SearchResult {
+        SearchResult {
+            found: false,
+            path: Vec::new(),
+            steps: Vec::new(),
+            occurrences: Vec::new(),
+            total_cost: 0.0,
+            best_attempts: self.best_attempts(),
+            total_states_explored: self.states_explored,
+            proven_optimal: false,
+            terminated_reason: self.terminated.unwrap_or(if self.states_pruned > 0 {
+                TerminatedReason::MemoryLimit
+            } else {
+                TerminatedReason::Exhausted
+            }),
+            states_pruned: self.states_pruned,
+        }
+    }
+    
+    fn best_attempts(&self) -> Vec<BestAttempt> {
+        self.best_attempts.iter()
+            .map(|attempt| BestAttempt {
+                text: self.interner.get(attempt.text).to_string(),
+                path: attempt.path.clone(),
+                distance: attempt.distance,
+            })
+            .collect()
+    }
+    
+    // Hit and miss counts of the distance and hint application caches
+    pub fn cache_stats(&self) -> CacheReport {
+        CacheReport {
+            distance: self.distance_cache.stats(),
+            decode: self.decode_cache.stats(),
+        }
+    }
+    
+    // Current progress of a search still running
+    pub fn progress(&self) -> ProgressUpdate {
+        let queue_size = self.queue.len() + self.backward_queue.len();
+        let progress_percentage = calculate_progress_percentage(
+            self.states_explored,
+            queue_size,
+            self.estimated_total_states
+        );
+        
+        // Calculate depth-based progress
+        let depth_progress = if self.config.max_depth > 0 {
+            (self.max_depth_reached as f64 / self.config.max_depth as f64 * 100.0).min(99.9)
+        } else {
+            0.0
+        };
+        
+        ProgressUpdate {
+            states_explored: self.states_explored,
+            current_best_distance: self.best_distance,
+            current_best_text: self.best_attempts.first()
+                .map(|a| self.interner.get(a.text).to_string())
+                .unwrap_or_default(),
+            queue_size,
+            progress_percentage,
+            estimated_total_states: self.estimated_total_states,
+            depth_progress,
+            max_depth_reached: self.max_depth_reached,
+        }
+    }
+}
+
+// Private implementation methods
+impl Pathfinder {
+    // Expand up to `iterations` states, returning the result once the target is reached
+    fn search(&mut self, iterations: usize) -> Option<SearchResult> {
+        for _ in 0..iterations {
+            if self.terminated.is_some() {
+                break;
+            }
+            if let Some(reason) = self.check_limits() {
+                self.terminated = Some(reason);
+                break;
+            }
+            
+            // Alternate directions by always expanding the smaller frontier
+            if self.config.mode == SearchMode::Bidirectional && self.should_expand_backward() {
+                if let Some(path) = self.expand_backward() {
+                    return Some(self.found_result(path));
+                }
+                continue;
+            }
+            
+            let current = match self.queue.pop() {
+                Some(state) => state,
+                None => break,
+            };
+            
+            if self.is_superseded(&current) {
+                continue;
+            }
+            if let Some(path) = self.expand_forward(current, None) {
+                return Some(self.found_result(path));
+            }
+        }
+        
+        None
+    }
+    
+    // Optimal search skips entries superseded by a route to the same state
+    // that is no more expensive and no deeper
+    fn is_superseded(&self, state: &SearchState) -> bool {
+        self.config.mode == SearchMode::Optimal
+            && self.best_cost.get(&(state.text, state.usage.clone()))
+                .is_some_and(|&best| dominates(best, (state.cost, state.depth)) && best != (state.cost, state.depth))
+    }
+    
+    // The target with every required hint used
+    fn is_goal(&self, state: &SearchState) -> bool {
+        state.text == self.target_id && self.usage_rules.remaining_required(&state.usage) == 0
+    }
+    
+    // Expand a state popped from the forward queue, returning the path in
+    // forward order once the target is reached. Successors are computed here
+    // unless they were computed ahead.
+    fn expand_forward(&mut self, current: SearchState, successors: Option<Vec<Successor>>) -> Option<Vec<Move>> {
+        self.states_explored += 1;
+        
+        // Track max depth reached
+        if current.depth > self.max_depth_reached {
+            self.max_depth_reached = current.depth;
+        }
+        
+        // Check if we found the target
+        if self.is_goal(&current) {
+            let path = self.hint_chain(current.node);
+            return Some(path.into_iter().rev().collect());
+        }
+        
+        // Update best attempts
+        self.update_best_attempts(current.text, current.node, current.depth, current.distance);
+        
+        // Skip if we've reached max depth
+        if current.depth >= self.config.max_depth {
+            return None;
+        }
+        
+        // Generate neighbors, which may meet the backward frontier
+        let successors = successors.unwrap_or_else(|| self.successors(&current));
+        if self.config.mode == SearchMode::Optimal {
+            self.generate_neighbors_optimal(&current, successors);
+            None
+        } else {
+            self.generate_neighbors(&current, successors)
+        }
+    }
+    
+    fn successors(&mut self, current: &SearchState) -> Vec<Successor> {
+        let text = self.interner.get(current.text);
+        let mut successors = Vec::new();
+        
+        // Only hints whose target occurs in the text can apply
+        for hint_index in self.matcher.candidates(&text) {
+            // Skip banned hints and hints used up on this path
+            let Some(usage) = self.usage_rules.advance(&current.usage, hint_index) else {
+                continue;
+            };
+            
+            // Apply hint, once per occurrence if it rewrites them separately
+            for (mv, new_text) in self.expand_hint(current.text, hint_index) {
+                successors.push(Successor { hint: hint_index, mv, text: new_text, usage: usage.clone(), distance: None });
+            }
+        }
+        
+        successors
+    }
+    
+    // Build the result for a path given as moves in forward order
+    fn found_result(&mut self, moves: Vec<Move>) -> SearchResult {
+        let steps = self.reconstruct_path(&moves);
+        let path: Vec<String> = moves.iter()
+            .map(|mv| self.hints[mv.hint].name.clone())
+            .collect();
+        
+        // Add to best attempts
+        self.record_best_attempt(self.target_id, path.clone(), 0.0);
+        
+        SearchResult {
+            found: true,
+            path,
+            steps,
+            occurrences: moves.iter().map(|mv| mv.position).collect(),
+            // Folded from 0.0, as an empty f64 sum is -0.0
+            total_cost: moves.iter().fold(0.0, |total, mv| total + self.hints[mv.hint].cost()),
+            best_attempts: self.best_attempts(),
+            total_states_explored: self.states_explored,
+            // Pruned states might have led to a cheaper path, and other
+            // partitions might still hold one
+            proven_optimal: self.config.mode == SearchMode::Optimal
+                && self.states_pruned == 0
+                && self.partition.is_none_or(|partition| partition.count == 1),
+            terminated_reason: TerminatedReason::Found,
+            states_pruned: self.states_pruned,
+        }
+    }
+    
+    fn generate_neighbors(&mut self, current: &SearchState, successors: Vec<Successor>) -> Option<Vec<Move>> {
+        for successor in successors {
+            let cost = current.cost + self.hints[successor.hint].cost();
+            if let Some(path) = self.add_successor(current.node, current.depth + 1, cost, successor) {
+                return Some(path);
+            }
+        }
+        
+        None
+    }
+    
+    // Queue a state reached from the node `parent`, unless another partition
+    // owns it. Returns the whole path if it meets the backward frontier.
+    fn add_successor(&mut self, parent: NodeId, depth: usize, cost: f64, successor: Successor) -> Option<Vec<Move>> {
+        let Successor { mv, text: new_text, usage, distance, .. } = successor;
+        if let Some(owner) = self.owner_elsewhere(&new_text) {
+            self.send(owner, parent, mv, new_text, cost, usage);
+            return None;
+        }
+        
+        // Skip if already visited
+        let known = self.interner.lookup(&new_text);
+        if self.already_reached(known, &usage, cost, depth) {
+            return None;
+        }
+        let penalty = self.dictionary_penalty(&new_text)?;
+        let new_id = known.unwrap_or_else(|| self.interner.intern(&new_text));
+        
+        // Stitch the two halves if the backward search already reached this text
+        if let Some(&(backward_node, backward_depth)) = self.backward_visited.get(&new_id) {
+            if depth + backward_depth <= self.config.max_depth {
+                let mut path: Vec<Move> = self.hint_chain(parent).into_iter().rev().collect();
+                path.push(mv);
+                path.extend(self.hint_chain(backward_node));
+                if self.path_allowed(&path) {
+                    return Some(path);
+                }
+            }
+        }
+        
+        // Calculate scores
+        let distance = match distance {
+            Some(distance) => distance,
+            None => self.calculate_distance(new_id, self.target_id),
+        };
+        
+        // Heuristic includes path cost to prefer cheaper paths
+        let heuristic_score = distance + cost * self.config.cost_weight + penalty;
+        
+        let node = self.push_node(Some(parent), mv);
+        if self.config.mode == SearchMode::Bidirectional {
+            self.forward_paths.insert(new_id, (node, depth));
+        }
+        
+        // Add to queue
+        let new_state = SearchState {
+            text: new_id,
+            node,
+            depth,
+            cost,
+            usage: usage.clone(),
+            distance,
+            heuristic_score,
+        };
+        
+        self.queue.push(new_state);
+        self.visited.insert((new_id, usage));
+        None
+    }
+    
+    // A* expansion: a text may be queued again when reached by a cheaper path
+    fn generate_neighbors_optimal(&mut self, current: &SearchState, successors: Vec<Successor>) {
+        for successor in successors {
+            let cost = current.cost + self.hints[successor.hint].cost();
+            self.add_successor_optimal(current.node, current.depth + 1, cost, successor);
+        }
+    }
+    
+    fn add_successor_optimal(&mut self, parent: NodeId, depth: usize, cost: f64, successor: Successor) {
+        let Successor { mv, text: new_text, usage, distance, .. } = successor;
+        if let Some(owner) = self.owner_elsewhere(&new_text) {
+            self.send(owner, parent, mv, new_text, cost, usage);
+            return;
+        }
+        
+        let known = self.interner.lookup(&new_text);
+        if self.already_reached(known, &usage, cost, depth) {
+            return;
+        }
+        if self.dictionary_penalty(&new_text).is_none() {
+            return;
+        }
+        
+        // Prune texts the remaining hints can never turn into the target
+        let bound = match self.optimal_bound(&new_text, &usage) {
+            Some(bound) => bound,
+            None => return,
+        };
+        if depth + bound > self.config.max_depth {
+            return;
+        }
+        
+        let new_id = known.unwrap_or_else(|| self.interner.intern(&new_text));
+        let distance = match distance {
+            Some(distance) => distance,
+            None => self.calculate_distance(new_id, self.target_id),
+        };
+        let node = self.push_node(Some(parent), mv);
+        
+        let key = (new_id, usage.clone());
+        self.best_cost.insert(key.clone(), (cost, depth));
+        self.visited.insert(key);
+        self.queue.push(SearchState {
+            text: new_id,
+            node,
+            depth,
+            cost,
+            usage,
+            distance,
+            heuristic_score: cost + bound as f64 * self.min_hint_cost,
+        });
+    }
+    
+    // Whether a state was reached before. Optimal search only counts routes
+    // no more expensive and no deeper: a cheaper route that takes more steps
+    // may leave too few for the rest of the path under `max_depth`.
+    fn already_reached(&self, text: Option<TextId>, usage: &Usage, cost: f64, depth: usize) -> bool {
+        let Some(text) = text else {
+            return false;
+        };
+        let key = (text, usage.clone());
+        if self.config.mode == SearchMode::Optimal {
+            self.best_cost.get(&key).is_some_and(|&best| dominates(best, (cost, depth)))
+        } else {
+            self.visited.contains(&key)
+        }
+    }
+    
+    fn should_expand_backward(&self) -> bool {
+        !self.backward_queue.is_empty()
+            && (self.queue.is_empty() || self.backward_queue.len() < self.queue.len())
+    }
+    
+    // Expand one state of the backward frontier. Backward nodes point towards
+    // the target, so their hint chains are already in forward order.
+    fn expand_backward(&mut self) -> Option<Vec<Move>> {
+        let current = self.backward_queue.pop()?;
+        self.states_explored += 1;
+        
+        if current.depth >= self.config.max_depth {
+            return None;
+        }
+        
+        let depth = current.depth + 1;
+        let text = self.interner.get(current.text);
+        for hint_index in 0..self.hints.len() {
+            if self.usage_rules.is_banned(hint_index) {
+                continue;
+            }
+            for (mv, prev_text) in self.invert_hint(&text, hint_index) {
+                let known = self.interner.lookup(&prev_text);
+                if known.is_some_and(|id| self.backward_visited.contains_key(&id)) {
+                    continue;
+                }
+                let Some(penalty) = self.dictionary_penalty(&prev_text) else {
+                    continue;
+                };
+                let prev_id = known.unwrap_or_else(|| self.interner.intern(&prev_text));
+                
+                // Stitch the two halves if the forward search already reached this text
+                if let Some(&(forward_node, forward_depth)) = self.forward_paths.get(&prev_id) {
+                    if forward_depth + depth <= self.config.max_depth {
+                        let mut path: Vec<Move> = self.hint_chain(forward_node).into_iter().rev().collect();
+                        path.push(mv);
+                        path.extend(self.hint_chain(current.node));
+                        if self.path_allowed(&path) {
+                            return Some(path);
+                        }
+                    }
+                }
+                
+                let distance = self.calculate_distance(prev_id, self.start_id);
+                let cost = current.cost + self.hints[hint_index].cost();
+                let heuristic_score = distance + cost * self.config.cost_weight + penalty;
+                let node = self.push_node(Some(current.node), mv);
+                
+                self.backward_visited.insert(prev_id, (node, depth));
+                self.backward_queue.push(SearchState {
+                    text: prev_id,
+                    node,
+                    depth,
+                    cost,
+                    usage: None,
+                    distance,
+                    heuristic_score,
+                });
+            }
+        }
+        
+        None
+    }
+    
+    // Reason to end the search if a limit has been reached, pruning the
+    // frontier first when that is allowed
+    fn check_limits(&mut self) -> Option<TerminatedReason> {
+        let limits = &self.config.limits;
+        if limits.max_states_explored.is_some_and(|max| self.states_explored >= max) {
+            return Some(TerminatedReason::StateLimit);
+        }
+        
+        let max_stored = limits.max_states_stored?;
+        if self.states_stored() < max_stored {
+            return None;
+        }
+        if limits.on_memory_limit == LimitAction::Prune {
+            self.prune_frontier((max_stored as f64 * PRUNE_TARGET) as usize);
+            if self.states_stored() < max_stored {
+                return None;
+            }
+        }
+        Some(TerminatedReason::MemoryLimit)
+    }
+    
+    fn states_stored(&self) -> usize {
+        self.visited.len() + self.backward_visited.len()
+    }
+    
+    // Drop the worst-scored frontier states until at most `target` states are
+    // stored. Dropped states are forgotten, so another path can reach them
+    // again. Expanded states stay stored, and at least half of the frontier is
+    // kept, so a search whose expanded states fill the limit stops instead of
+    // losing its whole frontier.
+    fn prune_frontier(&mut self, target: usize) {
+        let mut forward = std::mem::take(&mut self.queue).into_sorted_vec();
+        let mut backward = std::mem::take(&mut self.backward_queue).into_sorted_vec();
+        let max_pruned = (forward.len() + backward.len()) / 2;
+        
+        // Sorted vectors hold the worst states first; take from the larger frontier
+        let mut forward_start = 0;
+        let mut backward_start = 0;
+        while self.states_stored() > target && forward_start + backward_start < max_pruned {
+            let forward_left = forward.len() - forward_start;
+            let backward_left = backward.len() - backward_start;
+            if forward_left >= backward_left {
+                let state = &mut forward[forward_start];
+                let key = (state.text, state.usage.take());
+                // Stale optimal entries only need dropping; another entry owns the key
+                let stale = self.best_cost.get(&key).is_some_and(|&best| best != (state.cost, state.depth));
+                if !stale {
+                    self.visited.remove(&key);
+                    self.best_cost.remove(&key);
+                }
+                forward_start += 1;
+            } else {
+                self.backward_visited.remove(&backward[backward_start].text);
+                backward_start += 1;
+            }
+            self.states_pruned += 1;
+        }
+        
+        self.queue = forward.split_off(forward_start).into();
+        self.backward_queue = backward.split_off(backward_start).into();
+        self.compact_texts();
+        self.compact_nodes();
+    }
+    
+    // Drop interned texts no state refers to any more and renumber the rest,
+    // cache keys included
+    fn compact_texts(&mut self) {
+        let mut live = vec![false; self.interner.len()];
+        live[self.start_id as usize] = true;
+        live[self.target_id as usize] = true;
+        for &(text, _) in self.visited.iter().chain(self.best_cost.keys()) {
+            live[text as usize] = true;
+        }
+        for state in self.queue.iter().chain(self.backward_queue.iter()) {
+            live[state.text as usize] = true;
+        }
+        for &text in self.forward_paths.keys().chain(self.backward_visited.keys()) {
+            live[text as usize] = true;
+        }
+        for attempt in &self.best_attempts {
+            live[attempt.text as usize] = true;
+        }
+        
+        let new_ids = self.interner.retain(|id| live[id as usize]);
+        let remap = |id: TextId| new_ids[id as usize].expect("live texts keep an id");
+        
+        self.start_id = remap(self.start_id);
+        self.target_id = remap(self.target_id);
+        self.visited = self.visited.drain().map(|(text, usage)| (remap(text), usage)).collect();
+        self.best_cost = self.best_cost.drain().map(|((text, usage), cost)| ((remap(text), usage), cost)).collect();
+        // Ids take no part in the ordering, so the heap layouts stay valid
+        for queue in [&mut self.queue, &mut self.backward_queue] {
+            let mut states = std::mem::take(queue).into_vec();
+            for state in &mut states {
+                state.text = remap(state.text);
+            }
+            *queue = BinaryHeap::from(states);
+        }
+        self.forward_paths = self.forward_paths.drain().map(|(text, entry)| (remap(text), entry)).collect();
+        self.backward_visited = self.backward_visited.drain().map(|(text, entry)| (remap(text), entry)).collect();
+        for attempt in &mut self.best_attempts {
+            attempt.text = remap(attempt.text);
+        }
+        
+        // Entries of dropped texts go; the others keep their values
+        let new_id = |id: TextId| new_ids[id as usize];
+        self.distance_cache.remap_keys(|(text, other)| Some((new_id(text)?, new_id(other)?)));
+        self.decode_cache.remap_keys(|(text, hint)| Some((new_id(text)?, hint)));
+    }
+    
+    // Drop arena nodes no stored state leads back through and renumber the
+    // rest. A parent always comes before its children, so a backward pass
+    // reaches every ancestor and renumbering keeps that order. Node 0, the
+    // root of the start state, stays where partitions rebuild paths from.
+    fn compact_nodes(&mut self) {
+        let mut live = vec![false; self.nodes.len()];
+        live[0] = true;
+        for state in self.queue.iter().chain(self.backward_queue.iter()) {
+            live[state.node as usize] = true;
+        }
+        for &(node, _) in self.forward_paths.values().chain(self.backward_visited.values()) {
+            live[node as usize] = true;
+        }
+        for node in (0..self.nodes.len()).rev() {
+            if let (true, Some(parent)) = (live[node], self.nodes[node].parent) {
+                live[parent as usize] = true;
+            }
+        }
+        
+        let mut new_ids: Vec<Option<NodeId>> = vec![None; self.nodes.len()];
+        let mut nodes = Vec::with_capacity(live.iter().filter(|&&live| live).count());
+        for (node, entry) in self.nodes.iter().enumerate() {
+            if live[node] {
+                new_ids[node] = Some(nodes.len() as NodeId);
+                nodes.push(SearchNode {
+                    parent: entry.parent.map(|parent| new_ids[parent as usize].expect("parents come first")),
+                    ..*entry
+                });
+            }
+        }
+        self.nodes = nodes;
+        let remap = |node: NodeId| new_ids[node as usize].expect("live nodes keep an id");
+        
+        for queue in [&mut self.queue, &mut self.backward_queue] {
+            let mut states = std::mem::take(queue).into_vec();
+            for state in &mut states {
+                state.node = remap(state.node);
+            }
+            *queue = BinaryHeap::from(states);
+        }
+        for (node, _) in self.forward_paths.values_mut().chain(self.backward_visited.values_mut()) {
+            *node = remap(*node);
+        }
+    }
+    
+    // Lower bound for optimal search: the hints the text still needs, but at
+    // least the required hints the path has not used yet
+    fn optimal_bound(&self, text: &str, usage: &Usage) -> Option<usize> {
+        let bound = self.hint_bounds.lower_bound(text, &self.target)?;
+        Some(bound.max(self.usage_rules.remaining_required(usage)))
+    }
+    
+    // Whether a stitched bidirectional path keeps to the constraints; the
+    // backward half does not track hint usage, so the whole path is checked
+    fn path_allowed(&self, path: &[Move]) -> bool {
+        let hint_path: Vec<usize> = path.iter().map(|mv| mv.hint).collect();
+        self.usage_rules.allows_path(&hint_path)
+    }
+    
+    // Extra heuristic cost for an intermediate text, or None if the dictionary
+    // rules it out. The start and target are always allowed.
+    fn dictionary_penalty(&self, text: &str) -> Option<f64> {
+        let (dictionary, mode) = match &self.dictionary {
+            Some(dictionary) => dictionary,
+            None => return Some(0.0),
+        };
+        if text == self.start || text == self.target || dictionary.accepts(text) {
+            return Some(0.0);
+        }
+        match mode {
+            DictionaryMode::Require => None,
+            DictionaryMode::Prefer => Some(DICTIONARY_PENALTY),
+        }
+    }
+    
+    fn push_node(&mut self, parent: Option<NodeId>, mv: Move) -> NodeId {
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(SearchNode {
+            parent,
+            hint: mv.hint as u32,
+            position: mv.position.map(|position| position as u32),
+        });
+        id
+    }
+    
+    // Moves from `node` back to its root, nearest first
+    fn hint_chain(&self, mut node: NodeId) -> Vec<Move> {
+        let mut chain = Vec::new();
+        while let Some(parent) = self.nodes[node as usize].parent {
+            let entry = self.nodes[node as usize];
+            chain.push(Move {
+                hint: entry.hint as usize,
+                position: entry.position.map(|position| position as usize),
+            });
+            node = parent;
+        }
+        chain
+    }
+    
+    fn node_path(&self, node: NodeId) -> Vec<String> {
+        self.hint_chain(node).into_iter().rev()
+            .map(|mv| self.hints[mv.hint].name.clone())
+            .collect()
+    }
+    
+    fn occurrence_mode(&self, hint_index: usize) -> OccurrenceMode {
+        self.hints[hint_index].occurrences.unwrap_or(self.occurrences)
+    }
+    
+    // Texts reachable from `text` with one hint: a single move when the hint
+    // rewrites every occurrence, otherwise one move per occurrence
+    fn expand_hint(&mut self, text: TextId, hint_index: usize) -> Vec<(Move, String)> {
+        match self.occurrence_mode(hint_index) {
+            OccurrenceMode::All => self.apply_hint(text, hint_index)
+                .map(|new_text| (Move { hint: hint_index, position: None }, new_text))
+                .into_iter()
+                .collect(),
+            OccurrenceMode::Single => self.expand_text(&self.interner.get(text), hint_index),
+        }
+    }
+    
+    // `expand_hint` for a text that is not interned, bypassing the decode cache
+    fn expand_text(&self, text: &str, hint_index: usize) -> Vec<(Move, String)> {
+        let hint = &self.hints[hint_index];
+        match self.occurrence_mode(hint_index) {
+            OccurrenceMode::All => hint.apply(text)
+                .map(|new_text| (Move { hint: hint_index, position: None }, new_text))
+                .into_iter()
+                .collect(),
+            OccurrenceMode::Single => hint.occurrences_in(text).into_iter()
+                .filter_map(|position| {
+                    let new_text = hint.apply_at(text, position)?;
+                    Some((Move { hint: hint_index, position: Some(position) }, new_text))
+                })
+                .collect(),
+        }
+    }
+    
+    // Find texts that turn into `text` when `hint` is applied.
+    // A remove hint is inverted by inserting its target at a single position,
+    // a replace hint by turning every replacement back into the target, or
+    // just one of them when the hint rewrites a single occurrence.
+    fn invert_hint(&mut self, text: &str, hint_index: usize) -> Vec<(Move, String)> {
+        let mode = self.occurrence_mode(hint_index);
+        let operation = &self.hints[hint_index].operation;
+        let target = operation.target();
+        let replacement = operation.replacement();
+        
+        // Candidates with the character position the target ends up at
+        let mut candidates: Vec<(usize, String)> = Vec::new();
+        if replacement.is_empty() {
+            let boundaries = text.char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(text.len()));
+            for (position, i) in boundaries.enumerate() {
+                let candidate = format!("{}{}{}", &text[..i], target, &text[i..]);
+                if !candidates.iter().any(|(_, c)| *c == candidate) {
+                    candidates.push((position, candidate));
+                }
+            }
+        } else if mode == OccurrenceMode::Single {
+            for (position, (i, _)) in text.char_indices().enumerate() {
+                if text[i..].starts_with(&*replacement) {
+                    candidates.push((position, format!("{}{}{}", &text[..i], target, &text[i + replacement.len()..])));
+                }
+            }
+        } else if text.contains(&*replacement) {
+            candidates.push((0, text.replace(&*replacement, target)));
+        }
+        
+        // Keep only candidates that really decode back to `text`
+        candidates.into_iter()
+            .filter_map(|(position, candidate)| {
+                let mv = match mode {
+                    OccurrenceMode::All => Move { hint: hint_index, position: None },
+                    OccurrenceMode::Single => Move { hint: hint_index, position: Some(position) },
+                };
+                (self.apply_move(&candidate, mv).as_deref() == Some(text)).then_some((mv, candidate))
+            })
+            .collect()
+    }
+    
+    // Candidate texts are not interned, so this bypasses the decode cache
+    fn apply_move(&self, text: &str, mv: Move) -> Option<String> {
+        match mv.position {
+            Some(position) => self.hints[mv.hint].apply_at(text, position),
+            None => self.hints[mv.hint].apply(text),
+        }
+    }
+    
+    fn apply_hint(&mut self, text: TextId, hint_index: usize) -> Option<String> {
+        // Check cache
+        if let Some(cached) = self.decode_cache.get((text, hint_index)) {
+            return cached.clone();
+        }
+        
+        // Apply hint operation
+        let result = self.hints[hint_index].apply(&self.interner.get(text));
+        
+        // Cache result
+        self.decode_cache.insert((text, hint_index), result.clone());
+        result
+    }
+    
+    fn calculate_distance(&mut self, text: TextId, other: TextId) -> f64 {
+        // Check cache
+        if let Some(&cached) = self.distance_cache.get((text, other)) {
+            return cached;
+        }
+        
+        let distance = self.heuristic.distance(&self.interner.get(text), &self.interner.get(other));
+        
+        // Cache result
+        self.distance_cache.insert((text, other), distance);
+        distance
+    }
+    
+    fn update_best_attempts(&mut self, text: TextId, node: NodeId, depth: usize, distance: f64) {
+        // Update best distance
+        if distance < self.best_distance {
+            self.best_distance = distance;
+        }
+        
+        // Only rebuild the path when the attempt would be kept
+        let keep = match self.best_attempts.iter().find(|a| a.text == text) {
+            Some(existing) => depth < existing.path.len(),
+            None => self.best_attempts.len() < self.config.best_attempts
+                || self.best_attempts.last().is_some_and(|last| distance < last.distance),
+        };
+        if keep {
+            let path = self.node_path(node);
+            self.record_best_attempt(text, path, distance);
+        }
+    }
+    
+    fn record_best_attempt(&mut self, text: TextId, path: Vec<String>, distance: f64) {
+        // Check if already in best attempts
+        if let Some(pos) = self.best_attempts.iter().position(|a| a.text == text) {
+            // Update if shorter path
+            if path.len() < self.best_attempts[pos].path.len() {
+                self.best_attempts[pos] = TrackedAttempt { text, path, distance };
+            }
+        } else {
+            // Add new attempt
+            self.best_attempts.push(TrackedAttempt { text, path, distance });
+            
+            // Sort and keep the closest ones
+            self.best_attempts.sort_by(|a, b| {
+                a.distance.partial_cmp(&b.distance).unwrap_or(Ordering::Equal)
+            });
+            self.best_attempts.truncate(self.config.best_attempts);
+        }
+    }
+    
+    // Texts along the path, written the way the start was typed: characters
+    // a hint did not touch keep their surface form, rewritten ones are shown
+    // normalized
+    fn reconstruct_path(&self, moves: &[Move]) -> Vec<String> {
+        let mut surface = self.start_surface.clone();
+        let mut steps = vec![surface.concat()];
+        let mut current_text = self.start.clone();
+        
+        for mv in moves {
+            let hint = &self.hints[mv.hint];
+            let (positions, applied) = match mv.position {
+                Some(position) => (vec![position], hint.apply_at(&current_text, position)),
+                None => (hint.match_positions(&current_text), hint.apply(&current_text)),
+            };
+            let Some(new_text) = applied else {
+                continue;
+            };
+            
+            let target_len = hint.target_chars().len();
+            let replacement: Vec<String> = hint.operation.replacement().chars().map(String::from).collect();
+            for &position in positions.iter().rev() {
+                surface.splice(position..position + target_len, replacement.iter().cloned());
+            }
+            
+            // Fall back to the normalized text if the two ever disagree
+            if surface.len() != new_text.chars().count() {
+                surface = new_text.chars().map(String::from).collect();
+            }
+            current_text = new_text;
+            steps.push(surface.concat());
+        }
+        
+        steps
+    }
+}
+
+// Whether a route of cost and depth `a` is as good as `b` for optimal search
+fn dominates(a: (f64, usize), b: (f64, usize)) -> bool {
+    a.0 <= b.0 && a.1 <= b.1
+}
+
+// Cheapest hint, which scales the step-count lower bound into a cost bound
+fn min_hint_cost(hints: &[Hint]) -> f64 {
+    let cheapest = hints.iter().map(Hint::cost).fold(f64::INFINITY, f64::min);
+    if cheapest.is_finite() { cheapest } else { 1.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hint::HintOperation;
+    
+    // Apply every move of a found path again, checking each step it shows
+    fn replay(engine: &Pathfinder, result: &SearchResult) {
+        assert!(result.found);
+        assert_eq!(result.steps.len(), result.path.len() + 1);
+        for (i, name) in result.path.iter().enumerate() {
+            let hint = engine.hints.iter().find(|hint| hint.name == *name).unwrap();
+            let next = match result.occurrences[i] {
+                Some(position) => hint.apply_at(&result.steps[i], position),
+                None => hint.apply(&result.steps[i]),
+            };
+            assert_eq!(next.as_deref(), Some(result.steps[i + 1].as_str()), "{:?}", result.path);
+        }
+    }
+    
+    #[test]
+    fn decomposed_targets_rewrite_the_right_characters() {
+        // Without normalization the hint keeps its decomposed target, which
+        // still matches a single composed character
+        let mut remove_ga = Hint::new("が", HintOperation::remove("か\u{3099}"));
+        remove_ga.ignore_voicing = true;
+        let mut engine = Pathfinder::with_normalization("こうが", "こう", vec![remove_ga], 2, SearchMode::Heuristic, Normalization::none()).unwrap();
+        let result = engine.run();
+        assert!(result.found);
+        assert_eq!(result.steps, vec!["こうが", "こう"]);
+        
+        // The same hint matching exactly
+        let mut engine = Pathfinder::with_normalization("こうが", "こう", vec![Hint::new("が", HintOperation::remove("か\u{3099}"))], 2, SearchMode::Heuristic, Normalization::none()).unwrap();
+        assert!(engine.run().found);
+    }
+    
+    #[test]
+    fn pruning_frees_the_nodes_of_dropped_states() {
+        let hints = parse_hints(include_str!("../fixtures/hints.json")).unwrap();
+        let mut config = SearchConfig { max_depth: 8, ..SearchConfig::default() };
+        config.limits.max_states_stored = Some(500);
+        let mut engine = Pathfinder::with_config("たなぞのときこけしのた", "ぬぬ", hints, config).unwrap();
+        let result = engine.run();
+        assert!(result.states_pruned > 0);
+        
+        // Every node is the start or leads to a stored state
+        assert!(engine.nodes.len() <= engine.states_stored() + engine.queue.len() + 1, "{} nodes", engine.nodes.len());
+    }
+    
+    #[test]
+    fn single_occurrence_moves_record_their_position() {
+        let hints = vec![Hint::new("な", HintOperation::replace("た", "な")), Hint::new("か", HintOperation::remove("か"))];
+        for mode in [SearchMode::Heuristic, SearchMode::Bidirectional] {
+            let mut engine = Pathfinder::new("たかたか", "たかなか", hints.clone(), 3, mode).unwrap();
+            engine.set_occurrence_mode(OccurrenceMode::Single);
+            let result = engine.run();
+            assert_eq!(result.occurrences, vec![Some(2)], "{:?}", mode);
+            assert_eq!(result.steps, vec!["たかたか", "たかなか"]);
+            
+            // Both hints, each on one of two occurrences
+            let mut engine = Pathfinder::new("たかたか", "たなか", hints.clone(), 3, mode).unwrap();
+            engine.set_occurrence_mode(OccurrenceMode::Single);
+            let result = engine.run();
+            assert_eq!(result.path.len(), 2, "{:?}", mode);
+            assert!(result.occurrences.iter().all(Option::is_some));
+            replay(&engine, &result);
+        }
+    }
+    
+    #[test]
+    fn bidirectional_paths_replay_to_the_target() {
+        let hints = parse_hints(include_str!("../fixtures/hints.json")).unwrap();
+        let mut engine = Pathfinder::new("たなぞのときこけしのた", "なぞとき", hints, 10, SearchMode::Bidirectional).unwrap();
+        let result = engine.run();
+        replay(&engine, &result);
+        assert_eq!(result.steps.last().map(String::as_str), Some("なぞとき"));
+        
+        // The backward half turns the replacement back into the target
+        let hints = vec![Hint::new("が", HintOperation::replace("か", "が")), Hint::new("た", HintOperation::remove("た"))];
+        let mut engine = Pathfinder::new("たかた", "が", hints, 4, SearchMode::Bidirectional).unwrap();
+        let result = engine.run();
+        replay(&engine, &result);
+        let mut path = result.path.clone();
+        path.sort();
+        assert_eq!(path, vec!["が", "た"]);
+    }
+    
+    #[test]
+    fn bidirectional_halves_only_meet_within_the_depth_limit() {
+        let hints = ["あ", "い", "う"].map(|target| Hint::new(target, HintOperation::remove(target))).to_vec();
+        let run = |max_depth| Pathfinder::new("あいうえ", "え", hints.clone(), max_depth, SearchMode::Bidirectional).unwrap().run();
+        
+        // Each half can go two steps, but together they may only take two
+        assert!(!run(2).found);
+        let result = run(3);
+        assert!(result.found);
+        assert_eq!(result.path.len(), 3);
+    }
+    
+    #[test]
+    fn optimal_search_keeps_shallow_routes_the_depth_limit_needs() {
+        // あ→い→か is cheaper than あ→か but leaves too few steps for か→き→く
+        let hints = vec![
+            Hint { cost: Some(3.0), ..Hint::new("H", HintOperation::replace("あ", "か")) },
+            Hint::new("a", HintOperation::replace("あ", "い")),
+            Hint::new("b", HintOperation::replace("い", "か")),
+            Hint::new("c", HintOperation::replace("か", "き")),
+            Hint::new("d", HintOperation::replace("き", "く")),
+        ];
+        let result = Pathfinder::new("あ", "く", hints, 3, SearchMode::Optimal).unwrap().run();
+        assert!(result.found);
+        assert_eq!(result.path, vec!["H", "c", "d"]);
+        assert_eq!(result.total_cost, 5.0);
+    }
+    
+    #[test]
+    fn costly_hints_are_avoided_when_a_path_as_short_exists() {
+        use crate::hint::HintGroup;
+        let grouped = |name: &str, operation: HintOperation, group: HintGroup| Hint { group: Some(group), ..Hint::new(name, operation) };
+        // か→さ is a stretch; か→た reaches an equally close text
+        let hints = vec![
+            grouped("さ", HintOperation::replace("か", "さ"), HintGroup::D),
+            grouped("た", HintOperation::replace("か", "た"), HintGroup::A),
+            grouped("-さ", HintOperation::remove("さ"), HintGroup::A),
+            grouped("-た", HintOperation::remove("た"), HintGroup::A),
+        ];
+        for mode in [SearchMode::Heuristic, SearchMode::Optimal] {
+            let result = Pathfinder::new("かい", "い", hints.clone(), 4, mode).unwrap().run();
+            assert_eq!(result.path, vec!["た", "-た"], "{:?}", mode);
+            let summed: f64 = result.path.iter()
+                .map(|name| hints.iter().find(|hint| hint.name == *name).unwrap().cost())
+                .sum();
+            assert_eq!(result.total_cost, summed);
+            assert_eq!(result.total_cost, 2.0);
+        }
+        
+        let result = Pathfinder::new("い", "い", hints, 4, SearchMode::Optimal).unwrap().run();
+        assert!(result.found && result.path.is_empty());
+        assert!(result.total_cost.is_sign_positive());
+    }
+    
+    #[test]
+    fn optimal_search_finds_shorter_paths_than_the_heuristic() {
+        // か→さ gets closest first but leaves き to clear in two more steps
+        let hints = vec![
+            Hint::new("P", HintOperation::replace("か", "さ")),
+            Hint::new("K", HintOperation::replace("き", "く")),
+            Hint::new("U", HintOperation::remove("く")),
+            Hint::new("Q", HintOperation::replace("かき", "けけ")),
+            Hint::new("R", HintOperation::replace("けけ", "さ")),
+        ];
+        let heuristic = Pathfinder::new("かき", "さ", hints.clone(), 4, SearchMode::Heuristic).unwrap().run();
+        assert_eq!(heuristic.path, vec!["P", "K", "U"]);
+        let optimal = Pathfinder::new("かき", "さ", hints, 4, SearchMode::Optimal).unwrap().run();
+        assert_eq!(optimal.path, vec!["Q", "R"]);
+    }
+}
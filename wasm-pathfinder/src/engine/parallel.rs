@@ -0,0 +1,179 @@
+use std::sync::Arc;
+use rayon::prelude::*;
+
+use crate::constraints::Usage;
+use super::{Pathfinder, SearchMode, SearchResult, SearchStep, Successor};
+
+// States taken from the forward queue per worker thread in each batch
+const STATES_PER_THREAD: usize = 16;
+
+// Parallel frontier expansion for native builds. Each batch pops the best
+// forward states, applies hints to them and measures distances on all cores,
+// then merges the successors one state at a time in pop order with the same
+// code as the sequential search, so visited sets, stitching and pruning are
+// unchanged. The backward half of a bidirectional search still expands one
+// state at a time.
+impl Pathfinder {
+    // Like `step`, with forward states expanded in parallel
+    pub fn step_parallel(&mut self, iterations: usize) -> SearchStep {
+        match self.search_parallel(iterations) {
+            Some(result) => SearchStep::Found(result),
+            None => SearchStep::Progress(self.progress()),
+        }
+    }
+    
+    // Like `run`, with forward states expanded in parallel
+    pub fn run_parallel(&mut self) -> SearchResult {
+        while !self.is_complete() {
+            if let Some(result) = self.search_parallel(1000) {
+                return result;
+            }
+        }
+        self.result()
+    }
+    
+    fn search_parallel(&mut self, iterations: usize) -> Option<SearchResult> {
+        let batch_size = rayon::current_num_threads() * STATES_PER_THREAD;
+        let mut expanded = 0;
+        
+        while expanded < iterations {
+            if self.terminated.is_some() {
+                break;
+            }
+            // Limits are checked between batches only: pruning renumbers the
+            // texts the states of a batch refer to
+            if let Some(reason) = self.check_limits() {
+                self.terminated = Some(reason);
+                break;
+            }
+            
+            if self.config.mode == SearchMode::Bidirectional && self.should_expand_backward() {
+                expanded += 1;
+                if let Some(path) = self.expand_backward() {
+                    return Some(self.found_result(path));
+                }
+                continue;
+            }
+            
+            // Pop the batch, never past the exploration limit
+            let mut budget = batch_size.min(iterations - expanded);
+            if let Some(max) = self.config.limits.max_states_explored {
+                budget = budget.min(max.saturating_sub(self.states_explored)).max(1);
+            }
+            let mut batch = Vec::with_capacity(budget);
+            while batch.len() < budget {
+                let Some(state) = self.queue.pop() else {
+                    break;
+                };
+                if !self.is_superseded(&state) {
+                    batch.push(state);
+                }
+            }
+            if batch.is_empty() {
+                break;
+            }
+            
+            // Computing successors only reads the engine
+            let texts: Vec<Arc<str>> = batch.iter().map(|state| self.interner.get(state.text)).collect();
+            let engine = &*self;
+            let successors: Vec<Option<Vec<Successor>>> = batch.par_iter()
+                .zip(texts.par_iter())
+                .map(|(state, text)| {
+                    let expands = state.depth < engine.config.max_depth && !engine.is_goal(state);
+                    expands.then(|| engine.successors_of(text, &state.usage))
+                })
+                .collect();
+            
+            let mut states = batch.into_iter().zip(successors).enumerate();
+            while let Some((index, (current, successors))) = states.next() {
+                // A goal popped behind other states is only the cheapest once
+                // their successors are queued, so optimal search puts it back
+                // with the rest of the batch and pops again
+                if self.config.mode == SearchMode::Optimal && index > 0 && self.is_goal(&current) {
+                    self.queue.push(current);
+                    self.queue.extend(states.map(|(_, (state, _))| state));
+                    break;
+                }
+                
+                // Successors merged earlier in the batch may have superseded it
+                if self.is_superseded(&current) {
+                    continue;
+                }
+                expanded += 1;
+                if let Some(path) = self.expand_forward(current, successors) {
+                    return Some(self.found_result(path));
+                }
+            }
+        }
+        
+        None
+    }
+    
+    // `successors` without the engine's caches, so it can run on any thread
+    fn successors_of(&self, text: &str, usage: &Usage) -> Vec<Successor> {
+        let mut successors = Vec::new();
+        for hint_index in self.matcher.candidates(text) {
+            let Some(usage) = self.usage_rules.advance(usage, hint_index) else {
+                continue;
+            };
+            for (mv, new_text) in self.expand_text(text, hint_index) {
+                let distance = self.heuristic.distance(&new_text, &self.target);
+                successors.push(Successor { hint: hint_index, mv, text: new_text, usage: usage.clone(), distance: Some(distance) });
+            }
+        }
+        successors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::HintConstraints;
+    use crate::hint::parse_hints;
+    
+    const HINTS: &str = include_str!("../../fixtures/hints.json");
+    
+    fn engine(start: &str, target: &str, mode: SearchMode) -> Pathfinder {
+        Pathfinder::from_json(start, target, HINTS, 12, mode).unwrap()
+    }
+    
+    // Applying the path's hints to the start text gives the target
+    fn assert_reaches(start: &str, target: &str, result: &SearchResult) {
+        assert!(result.found);
+        let hints = parse_hints(HINTS).unwrap();
+        let mut text = start.to_string();
+        for name in &result.path {
+            let hint = hints.iter().find(|hint| &hint.name == name).unwrap();
+            text = hint.apply(&text).unwrap();
+        }
+        assert_eq!(text, target);
+    }
+    
+    #[test]
+    fn parallel_search_finds_valid_paths() {
+        let (start, target) = ("あかいとり", "かと");
+        for mode in [SearchMode::Heuristic, SearchMode::Bidirectional, SearchMode::Optimal] {
+            let sequential = engine(start, target, mode).run();
+            let parallel = engine(start, target, mode).run_parallel();
+            assert_reaches(start, target, &parallel);
+            if mode == SearchMode::Optimal {
+                assert!(parallel.proven_optimal);
+                assert_eq!(parallel.total_cost, sequential.total_cost);
+            }
+        }
+    }
+    
+    #[test]
+    fn parallel_search_keeps_constraints() {
+        let constraints = HintConstraints {
+            required: vec!["リトル".to_string(), "鳥居".to_string()],
+            required_in_order: true,
+            ..HintConstraints::default()
+        };
+        let mut engine = engine("あかいとり", "かと", SearchMode::Optimal);
+        engine.set_constraints(constraints).unwrap();
+        let result = engine.run_parallel();
+        assert_eq!(result.path, vec!["リトル", "鳥居", "贖い"]);
+        assert_eq!(result.total_cost, 4.5);
+    }
+}
@@ -0,0 +1,349 @@
+use serde::{Serialize, Deserialize};
+
+use crate::constraints::Usage;
+use crate::error::Error;
+use super::{Move, NodeId, Pathfinder, SearchMode, SearchResult, Successor, TerminatedReason};
+
+// Every state batch starts with this magic and a format version
+const BATCH_MAGIC: &[u8; 4] = b"TKPB";
+const BATCH_VERSION: u32 = 1;
+
+// Node of the start state, the root every received path is rebuilt from
+const START_NODE: NodeId = 0;
+
+// One of `count` engines that split a search, each usually in its own
+// worker. Every text is owned by exactly one partition, chosen by a hash of
+// the text; only the owner queues the text and keeps it in its visited set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Partition {
+    pub index: u32,
+    pub count: u32,
+}
+
+impl Partition {
+    pub fn new(index: u32, count: u32) -> Result<Partition, Error> {
+        if index >= count {
+            return Err(Error::InvalidPartition(format!("Partition {} out of {}", index, count)));
+        }
+        Ok(Partition { index, count })
+    }
+    
+    // FNV-1a rather than the std hasher, whose output may change between
+    // builds, so engines in different workers always agree on the owner
+    pub fn owner(&self, text: &str) -> u32 {
+        let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        (hash % u64::from(self.count)) as u32
+    }
+}
+
+// Serialized states for the partition `partition`, to be passed to its
+// engine's `import_states`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateBatch {
+    pub partition: u32,
+    pub data: Vec<u8>,
+}
+
+// A state handed to the partition that owns its text, with every move from
+// the start so the owner can rebuild its path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct TransferredState {
+    text: String,
+    moves: Vec<Move>,
+    cost: f64,
+    usage: Usage,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BatchContents {
+    partition: u32,
+    states: Vec<TransferredState>,
+}
+
+impl Pathfinder {
+    // Make this engine one partition of a search split between several
+    // engines created with the same arguments. States owned by another
+    // partition are collected for `take_outgoing` instead of being queued.
+    // Must be called before the search starts; the backward frontier of a
+    // bidirectional search cannot be split.
+    pub fn set_partition(&mut self, partition: Partition) -> Result<(), Error> {
+        if self.states_explored > 0 {
+            return Err(Error::InvalidPartition("The partition must be set before the search starts".to_string()));
+        }
+        if self.config.mode == SearchMode::Bidirectional {
+            return Err(Error::InvalidPartition("Bidirectional search cannot be partitioned".to_string()));
+        }
+        
+        // Only the owner of the start text begins with a state to expand
+        if partition.owner(&self.start) != partition.index {
+            self.queue.clear();
+            self.visited.clear();
+            self.best_cost.clear();
+        }
+        self.partition = Some(partition);
+        Ok(())
+    }
+    
+    // Batches of the states reached for other partitions since the last call
+    pub fn take_outgoing(&mut self) -> Vec<StateBatch> {
+        std::mem::take(&mut self.outgoing).into_iter()
+            .map(|(partition, states)| {
+                let mut data = Vec::new();
+                data.extend_from_slice(BATCH_MAGIC);
+                data.extend_from_slice(&BATCH_VERSION.to_le_bytes());
+                bincode::serialize_into(&mut data, &BatchContents { partition, states })
+                    .expect("state batches are always serializable");
+                StateBatch { partition, data }
+            })
+            .collect()
+    }
+    
+    // Queue the states of a batch another partition sent to this one. States
+    // this partition has already reached as cheaply are dropped; returns how
+    // many were queued.
+    pub fn import_states(&mut self, data: &[u8]) -> Result<usize, Error> {
+        let partition = self.partition
+            .ok_or_else(|| Error::InvalidPartition("The engine is not partitioned".to_string()))?;
+        if data.len() < 8 || &data[..4] != BATCH_MAGIC {
+            return Err(Error::InvalidPartition("Not a state batch".to_string()));
+        }
+        let version = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        if version != BATCH_VERSION {
+            return Err(Error::InvalidPartition(format!(
+                "Unsupported batch version {} (expected {})",
+                version,
+                BATCH_VERSION
+            )));
+        }
+        let contents: BatchContents = bincode::deserialize(&data[8..])
+            .map_err(|e| Error::InvalidPartition(format!("Failed to read state batch: {}", e)))?;
+        if contents.partition != partition.index {
+            return Err(Error::InvalidPartition(format!(
+                "Batch for partition {} sent to partition {}",
+                contents.partition,
+                partition.index
+            )));
+        }
+        
+        let queued_before = self.queue.len();
+        // Nodes of the last path rebuilt; states sent from the same parent
+        // arrive together and share them
+        let mut chain: Vec<(Move, NodeId)> = Vec::new();
+        for state in contents.states {
+            let Some((&mv, prefix)) = state.moves.split_last() else {
+                continue;
+            };
+            if state.moves.iter().any(|mv| mv.hint >= self.hints.len()) || state.moves.len() > self.config.max_depth {
+                return Err(Error::InvalidPartition("Batch does not match this engine's hints".to_string()));
+            }
+            
+            // Drop duplicates before rebuilding their path, so they add no nodes
+            if self.already_reached(self.interner.lookup(&state.text), &state.usage, state.cost, state.moves.len()) {
+                continue;
+            }
+            let shared = chain.iter().zip(prefix).take_while(|((a, _), b)| a == *b).count();
+            chain.truncate(shared);
+            for &mv in &prefix[shared..] {
+                let parent = chain.last().map_or(START_NODE, |&(_, node)| node);
+                chain.push((mv, self.push_node(Some(parent), mv)));
+            }
+            
+            let parent = chain.last().map_or(START_NODE, |&(_, node)| node);
+            let depth = state.moves.len();
+            let successor = Successor { hint: mv.hint, mv, text: state.text, usage: state.usage, distance: None };
+            if self.config.mode == SearchMode::Optimal {
+                self.add_successor_optimal(parent, depth, state.cost, successor);
+            } else {
+                self.add_successor(parent, depth, state.cost, successor);
+            }
+        }
+        Ok(self.queue.len().saturating_sub(queued_before))
+    }
+    
+    // Lowest score left in the queue. An optimal path found by one partition
+    // is proven once no partition has a lower bound and no batch is in flight.
+    pub fn frontier_bound(&self) -> Option<f64> {
+        self.queue.peek().map(|state| state.heuristic_score)
+    }
+    
+    // Partition owning `text` when that is not this engine
+    pub(super) fn owner_elsewhere(&self, text: &str) -> Option<u32> {
+        let partition = self.partition?;
+        let owner = partition.owner(text);
+        (owner != partition.index).then_some(owner)
+    }
+    
+    pub(super) fn send(&mut self, owner: u32, parent: NodeId, mv: Move, text: String, cost: f64, usage: Usage) {
+        let mut moves: Vec<Move> = self.hint_chain(parent).into_iter().rev().collect();
+        moves.push(mv);
+        self.outgoing.entry(owner).or_default().push(TransferredState { text, moves, cost, usage });
+    }
+}
+
+// Combine the results of the partitions of one search: the cheapest path
+// any of them found, the closest attempts of all of them and their totals
+pub fn merge_results(results: Vec<SearchResult>) -> SearchResult {
+    let attempts_kept = results.iter().map(|result| result.best_attempts.len()).max().unwrap_or(0);
+    let total_states_explored = results.iter().map(|result| result.total_states_explored).sum();
+    let states_pruned = results.iter().map(|result| result.states_pruned).sum();
+    let terminated_reason = [TerminatedReason::Found, TerminatedReason::StateLimit, TerminatedReason::MemoryLimit]
+        .into_iter()
+        .find(|reason| results.iter().any(|result| result.terminated_reason == *reason))
+        .unwrap_or(TerminatedReason::Exhausted);
+    
+    let mut best_attempts: Vec<_> = results.iter().flat_map(|result| result.best_attempts.iter().cloned()).collect();
+    best_attempts.sort_by(|a, b| a.distance.total_cmp(&b.distance).then_with(|| a.path.len().cmp(&b.path.len())));
+    let mut seen = std::collections::HashSet::new();
+    best_attempts.retain(|attempt| seen.insert(attempt.text.clone()));
+    best_attempts.truncate(attempts_kept);
+    
+    let found = results.into_iter()
+        .filter(|result| result.found)
+        .min_by(|a, b| a.total_cost.total_cmp(&b.total_cost).then_with(|| a.path.len().cmp(&b.path.len())));
+    match found {
+        Some(found) => SearchResult {
+            best_attempts,
+            total_states_explored,
+            states_pruned,
+            terminated_reason,
+            ..found
+        },
+        None => SearchResult {
+            found: false,
+            path: Vec::new(),
+            steps: Vec::new(),
+            occurrences: Vec::new(),
+            total_cost: 0.0,
+            best_attempts,
+            total_states_explored,
+            proven_optimal: false,
+            terminated_reason,
+            states_pruned,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SearchStep;
+    
+    const HINTS: &str = include_str!("../../fixtures/hints.json");
+    
+    fn partitions(start: &str, target: &str, mode: SearchMode, count: u32) -> Vec<Pathfinder> {
+        (0..count)
+            .map(|index| {
+                let mut engine = Pathfinder::from_json(start, target, HINTS, 12, mode).unwrap();
+                engine.set_partition(Partition::new(index, count).unwrap()).unwrap();
+                engine
+            })
+            .collect()
+    }
+    
+    // Step every partition in turn and relay their batches, the way the
+    // workers' host does. A greedy search takes the first path found; an
+    // optimal one keeps going until no partition could still find a cheaper
+    // path, or until every partition has run dry.
+    fn run_partitioned(engines: &mut [Pathfinder]) -> SearchResult {
+        let optimal = engines[0].config.mode == SearchMode::Optimal;
+        let mut best: Option<(usize, SearchResult)> = None;
+        loop {
+            for (index, engine) in engines.iter_mut().enumerate() {
+                if let SearchStep::Found(result) = engine.step(20) {
+                    if best.as_ref().is_none_or(|(_, best)| result.total_cost < best.total_cost) {
+                        best = Some((index, result));
+                    }
+                }
+            }
+            
+            let batches: Vec<StateBatch> = engines.iter_mut().flat_map(|engine| engine.take_outgoing()).collect();
+            for batch in batches {
+                engines[batch.partition as usize].import_states(&batch.data).unwrap();
+            }
+            
+            let settled = best.as_ref().is_some_and(|(_, best)| {
+                !optimal || engines.iter().all(|engine| engine.frontier_bound().is_none_or(|bound| bound >= best.total_cost))
+            });
+            if settled || engines.iter().all(|engine| engine.is_complete()) {
+                let mut results: Vec<SearchResult> = engines.iter().map(|engine| engine.result()).collect();
+                if let Some((index, found)) = best {
+                    results[index] = found;
+                }
+                return merge_results(results);
+            }
+        }
+    }
+    
+    #[test]
+    fn partitions_agree_on_owners() {
+        for text in ["あかいとり", "かと", "なぞとき", "たなぞのときこけしのた"] {
+            let owners: Vec<u32> = (0..3).map(|index| Partition::new(index, 3).unwrap().owner(text)).collect();
+            assert!(owners[0] < 3);
+            assert!(owners.iter().all(|&owner| owner == owners[0]));
+        }
+        assert!(Partition::new(3, 3).is_err());
+    }
+    
+    #[test]
+    fn partitioned_search_finds_the_cheapest_path() {
+        let sequential = Pathfinder::from_json("あかいとり", "かと", HINTS, 12, SearchMode::Optimal).unwrap().run();
+        for count in [1, 2, 3] {
+            let mut engines = partitions("あかいとり", "かと", SearchMode::Optimal, count);
+            let result = run_partitioned(&mut engines);
+            assert!(result.found);
+            assert_eq!(result.total_cost, sequential.total_cost);
+            assert_eq!(result.steps.last().map(String::as_str), Some("かと"));
+        }
+    }
+    
+    #[test]
+    fn partitions_split_the_visited_states() {
+        let mut engines = partitions("たなぞのときこけしのた", "なぞとき", SearchMode::Heuristic, 3);
+        let result = run_partitioned(&mut engines);
+        assert!(result.found);
+        assert_eq!(result.steps.last().map(String::as_str), Some("なぞとき"));
+        
+        // No text is visited by two partitions, and each owns what it visited
+        for (index, engine) in engines.iter().enumerate() {
+            for &(text, _) in &engine.visited {
+                assert_eq!(engine.partition.unwrap().owner(&engine.interner.get(text)), index as u32);
+            }
+        }
+    }
+    
+    #[test]
+    fn exhausted_partitions_merge_their_attempts() {
+        let mut engines = partitions("あかいとり", "ぬぬ", SearchMode::Heuristic, 2);
+        for engine in &mut engines {
+            engine.config.max_depth = 3;
+        }
+        let result = run_partitioned(&mut engines);
+        assert!(!result.found);
+        assert_eq!(result.terminated_reason, TerminatedReason::Exhausted);
+        assert_eq!(result.total_states_explored, engines.iter().map(|engine| engine.states_explored).sum::<usize>());
+        assert!(result.best_attempts.windows(2).all(|pair| pair[0].distance <= pair[1].distance));
+    }
+    
+    #[test]
+    fn rejects_batches_for_other_partitions() {
+        let mut engines = partitions("たなぞのときこけしのた", "なぞとき", SearchMode::Heuristic, 2);
+        let owner = Partition::new(0, 2).unwrap().owner("たなぞのときこけしのた") as usize;
+        engines[owner].step(1);
+        let batch = engines[owner].take_outgoing().pop().expect("some successor belongs to the other partition");
+        
+        assert!(engines[owner].import_states(&batch.data).is_err());
+        assert!(engines[batch.partition as usize].import_states(&batch.data).unwrap() > 0);
+        assert!(engines[0].import_states(b"nope").is_err());
+        
+        // A batch received twice queues nothing and leaves no nodes behind
+        let receiver = &mut engines[batch.partition as usize];
+        let nodes = receiver.nodes.len();
+        assert_eq!(receiver.import_states(&batch.data).unwrap(), 0);
+        assert_eq!(receiver.nodes.len(), nodes);
+        
+        let mut bidirectional = Pathfinder::from_json("たこ", "こ", HINTS, 4, SearchMode::Bidirectional).unwrap();
+        assert!(bidirectional.set_partition(Partition::new(0, 2).unwrap()).is_err());
+    }
+}
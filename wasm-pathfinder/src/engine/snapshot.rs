@@ -0,0 +1,236 @@
+use std::collections::BinaryHeap;
+use serde::{Serialize, Deserialize};
+
+use crate::bounds::HintBounds;
+use crate::cache::ClockCache;
+use crate::config::SearchConfig;
+use crate::constraints::{HintConstraints, UsageRules};
+use crate::dictionary::{Dictionary, DictionaryData, DictionaryMode};
+use crate::error::Error;
+use crate::hint::{Hint, OccurrenceMode};
+use crate::interner::{Interner, TextId};
+use crate::matcher::HintMatcher;
+use super::partition::TransferredState;
+use super::{min_hint_cost, NodeId, Partition, Pathfinder, SearchNode, SearchState, StateKey, TerminatedReason, TrackedAttempt};
+
+// Every exported state starts with this magic and a format version
+const STATE_MAGIC: &[u8; 4] = b"TKPF";
+const STATE_VERSION: u32 = 11;
+
+// Everything needed to continue a search; caches are rebuilt on demand
+#[derive(Serialize, Deserialize)]
+struct EngineSnapshot {
+    // Search parameters
+    start: String,
+    target: String,
+    config: SearchConfig,
+    start_surface: Vec<String>,
+    occurrences: OccurrenceMode,
+    constraints: HintConstraints,
+    // Tagged hint operations need a self-describing format, so hints stay JSON
+    hints_json: String,
+    // Interned texts in id order, so the ids below keep their meaning
+    texts: Vec<String>,
+    start_id: TextId,
+    target_id: TextId,
+    
+    // Core data structures, with queues in heap order
+    queue: Vec<SearchState>,
+    visited: Vec<StateKey>,
+    nodes: Vec<SearchNode>,
+    
+    // Tracking
+    best_attempts: Vec<TrackedAttempt>,
+    best_distance: f64,
+    states_explored: usize,
+    estimated_total_states: usize,
+    max_depth_reached: usize,
+    states_pruned: usize,
+    terminated: Option<TerminatedReason>,
+    
+    // Bidirectional and optimal search
+    forward_paths: Vec<(TextId, (NodeId, usize))>,
+    backward_queue: Vec<SearchState>,
+    backward_visited: Vec<(TextId, (NodeId, usize))>,
+    best_cost: Vec<(StateKey, (f64, usize))>,
+    dictionary: Option<(DictionaryData, DictionaryMode)>,
+    
+    // Partitioned search, with states not yet sent to their owners
+    partition: Option<Partition>,
+    outgoing: Vec<(u32, Vec<TransferredState>)>,
+}
+
+impl Pathfinder {
+    // Save the queue, visited set, best attempts and counters in a versioned
+    // binary format, so a search can be checkpointed and resumed later
+    pub fn export_state(&self) -> Vec<u8> {
+        let snapshot = EngineSnapshot {
+            start: self.start.clone(),
+            target: self.target.clone(),
+            config: self.config.clone(),
+            start_surface: self.start_surface.clone(),
+            occurrences: self.occurrences,
+            constraints: self.constraints.clone(),
+            hints_json: serde_json::to_string(&self.hints)
+                .expect("hints are always serializable"),
+            texts: self.interner.texts(),
+            start_id: self.start_id,
+            target_id: self.target_id,
+            queue: self.queue.iter().cloned().collect(),
+            visited: self.visited.iter().cloned().collect(),
+            nodes: self.nodes.clone(),
+            best_attempts: self.best_attempts.clone(),
+            best_distance: self.best_distance,
+            states_explored: self.states_explored,
+            estimated_total_states: self.estimated_total_states,
+            max_depth_reached: self.max_depth_reached,
+            states_pruned: self.states_pruned,
+            terminated: self.terminated,
+            forward_paths: self.forward_paths.iter().map(|(&k, &v)| (k, v)).collect(),
+            backward_queue: self.backward_queue.iter().cloned().collect(),
+            backward_visited: self.backward_visited.iter().map(|(&k, &v)| (k, v)).collect(),
+            best_cost: self.best_cost.iter().map(|(k, &v)| (k.clone(), v)).collect(),
+            dictionary: self.dictionary.as_ref().map(|(dictionary, mode)| (dictionary.to_data(), *mode)),
+            partition: self.partition,
+            outgoing: self.outgoing.iter().map(|(&k, v)| (k, v.clone())).collect(),
+        };
+        
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(STATE_MAGIC);
+        bytes.extend_from_slice(&STATE_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, &snapshot)
+            .expect("engine state is always serializable");
+        bytes
+    }
+    
+    // Restore an engine saved with `export_state`
+    pub fn from_state(bytes: &[u8]) -> Result<Pathfinder, Error> {
+        if bytes.len() < 8 || &bytes[..4] != STATE_MAGIC {
+            return Err(Error::InvalidState("Not a pathfinder state".to_string()));
+        }
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        if version != STATE_VERSION {
+            return Err(Error::InvalidState(format!(
+                "Unsupported state version {} (expected {})",
+                version,
+                STATE_VERSION
+            )));
+        }
+        
+        let snapshot: EngineSnapshot = bincode::deserialize(&bytes[8..])
+            .map_err(|e| Error::InvalidState(format!("Failed to read state: {}", e)))?;
+        let hints: Vec<Hint> = serde_json::from_str(&snapshot.hints_json)
+            .map_err(|e| Error::InvalidState(format!("Failed to read state hints: {}", e)))?;
+        let hint_bounds = HintBounds::new(&hints);
+        let matcher = HintMatcher::new(&hints);
+        let min_hint_cost = min_hint_cost(&hints);
+        let heuristic = snapshot.config.heuristic.build(snapshot.config.distance);
+        let distance_cache = ClockCache::new(snapshot.config.distance_cache_capacity);
+        let decode_cache = ClockCache::new(snapshot.config.decode_cache_capacity);
+        let usage_rules = UsageRules::new(&snapshot.constraints, &hints)
+            .map_err(|e| Error::InvalidState(format!("Failed to read state constraints: {}", e)))?;
+        let dictionary = match snapshot.dictionary {
+            Some((data, mode)) => Some((Dictionary::from_data(data)?, mode)),
+            None => None,
+        };
+        
+        // The queues were written in heap order, so rebuilding them keeps the pop order
+        Ok(Pathfinder {
+            queue: BinaryHeap::from(snapshot.queue),
+            visited: snapshot.visited.into_iter().collect(),
+            nodes: snapshot.nodes,
+            hints,
+            interner: Interner::from_texts(snapshot.texts),
+            start: snapshot.start,
+            target: snapshot.target,
+            start_id: snapshot.start_id,
+            target_id: snapshot.target_id,
+            config: snapshot.config,
+            start_surface: snapshot.start_surface,
+            occurrences: snapshot.occurrences,
+            constraints: snapshot.constraints,
+            usage_rules,
+            best_attempts: snapshot.best_attempts,
+            best_distance: snapshot.best_distance,
+            states_explored: snapshot.states_explored,
+            estimated_total_states: snapshot.estimated_total_states,
+            max_depth_reached: snapshot.max_depth_reached,
+            states_pruned: snapshot.states_pruned,
+            terminated: snapshot.terminated,
+            heuristic,
+            distance_cache,
+            decode_cache,
+            forward_paths: snapshot.forward_paths.into_iter().collect(),
+            backward_queue: BinaryHeap::from(snapshot.backward_queue),
+            backward_visited: snapshot.backward_visited.into_iter().collect(),
+            best_cost: snapshot.best_cost.into_iter().collect(),
+            hint_bounds,
+            matcher,
+            min_hint_cost,
+            dictionary,
+            partition: snapshot.partition,
+            outgoing: snapshot.outgoing.into_iter().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SearchMode;
+    
+    const HINTS: &str = include_str!("../../fixtures/hints.json");
+    
+    // Step both engines one state at a time and check they stay in lockstep
+    fn assert_same_run(uninterrupted: &mut Pathfinder, resumed: &mut Pathfinder, steps: usize) {
+        for _ in 0..steps {
+            assert_eq!(
+                uninterrupted.queue.peek().map(|s| (uninterrupted.interner.get(s.text), s.depth)),
+                resumed.queue.peek().map(|s| (resumed.interner.get(s.text), s.depth))
+            );
+            let expected = uninterrupted.search(1).map(|r| r.path);
+            let actual = resumed.search(1).map(|r| r.path);
+            assert_eq!(expected, actual);
+            assert_eq!(uninterrupted.states_explored, resumed.states_explored);
+            assert_eq!(uninterrupted.queue.len(), resumed.queue.len());
+            if expected.is_some() {
+                break;
+            }
+        }
+        
+        let texts = |engine: &Pathfinder| {
+            engine.best_attempts().into_iter().map(|a| a.text).collect::<Vec<_>>()
+        };
+        assert_eq!(texts(uninterrupted), texts(resumed));
+    }
+    
+    #[test]
+    fn resumed_search_explores_the_same_states() {
+        for mode in [SearchMode::Heuristic, SearchMode::Bidirectional, SearchMode::Optimal] {
+            let start = "たなぞのときこけしのた";
+            let target = "なぞとき";
+            let mut uninterrupted = Pathfinder::from_json(start, target, HINTS, 12, mode).unwrap();
+            let mut interrupted = Pathfinder::from_json(start, target, HINTS, 12, mode).unwrap();
+            
+            uninterrupted.search(4);
+            interrupted.search(4);
+            
+            let bytes = interrupted.export_state();
+            drop(interrupted);
+            let mut resumed = Pathfinder::from_state(&bytes).unwrap();
+            
+            assert_same_run(&mut uninterrupted, &mut resumed, 300);
+        }
+    }
+    
+    #[test]
+    fn rejects_unknown_state_versions() {
+        let engine = Pathfinder::from_json("たこ", "こ", HINTS, 4, SearchMode::Heuristic).unwrap();
+        let mut bytes = engine.export_state();
+        bytes[4] = 99;
+        
+        let error = Pathfinder::from_state(&bytes).err().expect("version 99 is rejected");
+        assert!(error.to_string().contains("version 99"));
+        assert!(Pathfinder::from_state(b"nope").is_err());
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+use crate::bounds::HintBounds;
+use crate::error::Error;
+use crate::hint::{parse_hints, validate_hints, Hint};
+use crate::matcher::HintMatcher;
+
+// One hint sequence that turns the start into the target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Solution {
+    pub path: Vec<String>,
+    pub steps: Vec<String>,
+}
+
+// Batch of newly completed solutions
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnumerationUpdate {
+    pub solutions: Vec<Solution>,
+    pub total_solutions: usize,
+    pub states_explored: usize,
+    pub current_depth: usize,
+    pub complete: bool,
+}
+
+// A text reached at a given depth, with every (parent, hint) pair that leads to it
+#[derive(Debug)]
+struct LayerNode {
+    text: String,
+    parents: Vec<(usize, usize)>,
+}
+
+// Breadth-first enumeration of every distinct hint sequence reaching the target.
+//
+// States are keyed by (text, depth) rather than text alone, so routes of any
+// length up to `max_depth` are kept. Layer `d` is complete once layer `d - 1`
+// has been expanded, which is when solutions of length `d` are emitted; they
+// therefore come out shortest first.
+pub struct SolutionEnumerator {
+    hints: Vec<Hint>,
+    hint_bounds: HintBounds,
+    matcher: HintMatcher,
+    target: String,
+    max_depth: usize,
+    max_solutions: usize,
+    
+    // Completed layers, the one being built and the next node to expand
+    layers: Vec<Vec<LayerNode>>,
+    next_layer: Vec<LayerNode>,
+    next_layer_index: HashMap<String, usize>,
+    cursor: usize,
+    
+    // Tracking
+    solutions_found: usize,
+    states_explored: usize,
+    complete: bool,
+}
+
+impl SolutionEnumerator {
+    // `max_solutions` caps how many solutions are produced; 0 means no limit
+    pub fn new(start: &str, target: &str, hints: Vec<Hint>, max_depth: usize, max_solutions: usize) -> Result<SolutionEnumerator, Error> {
+        validate_hints(&hints)?;
+        let hint_bounds = HintBounds::new(&hints);
+        let matcher = HintMatcher::new(&hints);
+        
+        Ok(SolutionEnumerator {
+            hints,
+            hint_bounds,
+            matcher,
+            target: target.to_string(),
+            max_depth,
+            max_solutions: if max_solutions == 0 { usize::MAX } else { max_solutions },
+            layers: Vec::new(),
+            next_layer: vec![LayerNode { text: start.to_string(), parents: Vec::new() }],
+            next_layer_index: HashMap::new(),
+            cursor: 0,
+            solutions_found: 0,
+            states_explored: 0,
+            complete: false,
+        })
+    }
+    
+    // Parse the hints from JSON and create an enumerator
+    pub fn from_json(start: &str, target: &str, hints_json: &str, max_depth: usize, max_solutions: usize) -> Result<SolutionEnumerator, Error> {
+        Self::new(start, target, parse_hints(hints_json)?, max_depth, max_solutions)
+    }
+    
+    // Check if enumeration is complete
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+    
+    // Expand up to `iterations` states and return the solutions completed meanwhile
+    pub fn enumerate(&mut self, iterations: usize) -> EnumerationUpdate {
+        let mut solutions = Vec::new();
+        
+        for _ in 0..iterations {
+            if self.complete {
+                break;
+            }
+            
+            // Finish the layer under construction once the current one is expanded
+            let layer_done = self.layers.last().is_none_or(|layer| self.cursor == layer.len());
+            if layer_done {
+                self.finish_layer(&mut solutions);
+                continue;
+            }
+            
+            let depth = self.layers.len() - 1;
+            let parent = self.cursor;
+            self.cursor += 1;
+            self.states_explored += 1;
+            self.expand(depth, parent);
+        }
+        
+        EnumerationUpdate {
+            solutions,
+            total_solutions: self.solutions_found,
+            states_explored: self.states_explored,
+            current_depth: self.layers.len().saturating_sub(1),
+            complete: self.complete,
+        }
+    }
+}
+
+// Private implementation methods
+impl SolutionEnumerator {
+    fn finish_layer(&mut self, solutions: &mut Vec<Solution>) {
+        let layer = std::mem::take(&mut self.next_layer);
+        self.next_layer_index.clear();
+        self.cursor = 0;
+        
+        if layer.is_empty() {
+            self.complete = true;
+            return;
+        }
+        
+        let target_index = layer.iter().position(|node| node.text == self.target);
+        self.layers.push(layer);
+        let depth = self.layers.len() - 1;
+        
+        // Every route into this layer is known now, so its solutions are final
+        if let Some(index) = target_index {
+            let limit = solutions.len().saturating_add(self.max_solutions - self.solutions_found);
+            let before = solutions.len();
+            self.collect_paths(depth, index, &mut Vec::new(), limit, solutions);
+            self.solutions_found += solutions.len() - before;
+        }
+        
+        if self.solutions_found >= self.max_solutions || depth >= self.max_depth {
+            self.complete = true;
+        }
+    }
+    
+    fn expand(&mut self, depth: usize, index: usize) {
+        let text = self.layers[depth][index].text.clone();
+        
+        // The target ends a solution; routes through it are not extended
+        if text == self.target {
+            return;
+        }
+        
+        // Only hints whose target occurs in the text can apply
+        for hint_index in self.matcher.candidates(&text) {
+            for new_text in self.hints[hint_index].applications(&text) {
+                // Drop texts that cannot reach the target in the remaining depth
+                match self.hint_bounds.lower_bound(&new_text, &self.target) {
+                    Some(bound) if depth + 1 + bound <= self.max_depth => {},
+                    _ => continue,
+                }
+                
+                let next_layer = &mut self.next_layer;
+                let node_index = *self.next_layer_index.entry(new_text).or_insert_with_key(|new_text| {
+                    next_layer.push(LayerNode { text: new_text.clone(), parents: Vec::new() });
+                    next_layer.len() - 1
+                });
+                self.next_layer[node_index].parents.push((index, hint_index));
+            }
+        }
+    }
+    
+    // Walk parent links back to the start, collecting up to `limit` paths.
+    // `suffix` holds (hint index, node index) pairs from the node back towards the start.
+    fn collect_paths(
+        &self,
+        depth: usize,
+        index: usize,
+        suffix: &mut Vec<(usize, usize)>,
+        limit: usize,
+        out: &mut Vec<Solution>
+    ) {
+        if out.len() >= limit {
+            return;
+        }
+        
+        if depth == 0 {
+            let mut path = Vec::with_capacity(suffix.len());
+            let mut steps = vec![self.layers[0][index].text.clone()];
+            for (step_depth, &(hint_index, node_index)) in suffix.iter().rev().enumerate() {
+                path.push(self.hints[hint_index].name.clone());
+                steps.push(self.layers[step_depth + 1][node_index].text.clone());
+            }
+            out.push(Solution { path, steps });
+            return;
+        }
+        
+        for &(parent, hint_index) in &self.layers[depth][index].parents {
+            suffix.push((hint_index, index));
+            self.collect_paths(depth - 1, parent, suffix, limit, out);
+            suffix.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hint::{HintOperation, OccurrenceMode};
+    
+    fn run(enumerator: &mut SolutionEnumerator) -> Vec<Solution> {
+        let mut solutions = Vec::new();
+        while !enumerator.is_complete() {
+            solutions.extend(enumerator.enumerate(100).solutions);
+        }
+        solutions
+    }
+    
+    fn hints() -> Vec<Hint> {
+        vec![Hint::new("a", HintOperation::remove("あ")), Hint::new("i", HintOperation::remove("い")), Hint::new("ai", HintOperation::remove("あい"))]
+    }
+    
+    fn paths(solutions: &[Solution]) -> Vec<Vec<&str>> {
+        solutions.iter()
+            .map(|solution| solution.path.iter().map(String::as_str).collect())
+            .collect()
+    }
+    
+    #[test]
+    fn every_route_comes_out_shortest_first() {
+        let mut enumerator = SolutionEnumerator::new("あいう", "う", hints(), 3, 0).unwrap();
+        let mut solutions = run(&mut enumerator);
+        assert_eq!(paths(&solutions[..1]), vec![vec!["ai"]]);
+        
+        // Both orders reach "う" at depth 2, so that node has two parents
+        solutions[1..].sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(paths(&solutions), vec![vec!["ai"], vec!["a", "i"], vec!["i", "a"]]);
+        assert_eq!(solutions[1].steps, vec!["あいう", "いう", "う"]);
+    }
+    
+    #[test]
+    fn stops_at_the_solution_cap() {
+        let mut enumerator = SolutionEnumerator::new("あいう", "う", hints(), 3, 2).unwrap();
+        let solutions = run(&mut enumerator);
+        assert_eq!(solutions.len(), 2);
+        assert_eq!(solutions[0].path, vec!["ai"]);
+    }
+    
+    #[test]
+    fn solutions_stream_out_layer_by_layer() {
+        let mut enumerator = SolutionEnumerator::new("あいう", "う", hints(), 3, 0).unwrap();
+        let mut updates = Vec::new();
+        while !enumerator.is_complete() {
+            updates.push(enumerator.enumerate(1));
+        }
+        
+        // The shortest solution is sent before the next layer is expanded
+        let batches: Vec<(usize, usize)> = updates.iter().enumerate()
+            .filter(|(_, update)| !update.solutions.is_empty())
+            .map(|(index, update)| (index, update.solutions.len()))
+            .collect();
+        assert_eq!(batches.iter().map(|&(_, count)| count).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(batches[0].0 < batches[1].0);
+        
+        let last = updates.last().unwrap();
+        assert!(last.complete);
+        assert_eq!(last.total_solutions, 3);
+        assert_eq!(last.current_depth, 2);
+    }
+    
+    #[test]
+    fn single_occurrence_hints_branch_over_occurrences() {
+        let remove_ko = Hint { occurrences: Some(OccurrenceMode::Single), ..Hint::new("こ", HintOperation::remove("こ")) };
+        let mut enumerator = SolutionEnumerator::new("こあこ", "あ", vec![remove_ko], 2, 0).unwrap();
+        let mut solutions = run(&mut enumerator);
+        solutions.sort_by(|a, b| a.steps.cmp(&b.steps));
+        let steps: Vec<Vec<String>> = solutions.into_iter().map(|solution| solution.steps).collect();
+        assert_eq!(steps, vec![vec!["こあこ", "あこ", "あ"], vec!["こあこ", "こあ", "あ"]]);
+    }
+    
+    #[test]
+    fn hints_ignoring_voicing_are_tried_on_marked_kana() {
+        let mut remove_ka = Hint::new("か", HintOperation::remove("か"));
+        remove_ka.ignore_voicing = true;
+        let mut enumerator = SolutionEnumerator::new("がみ", "み", vec![remove_ka], 2, 0).unwrap();
+        let solutions = run(&mut enumerator);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].steps, vec!["がみ", "み"]);
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt;
+
+// Errors reported by the engine
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    // Hints JSON that is malformed or contains an unusable hint
+    InvalidHints(String),
+    // Saved engine state that cannot be restored
+    InvalidState(String),
+    // Answer and hints that cannot be turned into a question
+    Encoding(String),
+    // Word list that cannot be loaded
+    InvalidDictionary(String),
+    // Hint constraints naming unknown hints or contradicting each other
+    InvalidConstraints(String),
+    // Partition settings or state batches that do not fit the engine
+    InvalidPartition(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidHints(message)
+            | Error::InvalidState(message)
+            | Error::Encoding(message)
+            | Error::InvalidDictionary(message)
+            | Error::InvalidConstraints(message)
+            | Error::InvalidPartition(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
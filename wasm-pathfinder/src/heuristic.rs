@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+use crate::distance::{levenshtein, weighted_distance_with, DistanceWeights};
+
+// Estimate of how far a search text is from the target; lower is closer.
+// The heuristic and bidirectional modes expand the closest texts first.
+// Implement it to plug an estimate of your own into `Pathfinder::with_heuristic`.
+pub trait Heuristic: Send + Sync {
+    fn distance(&self, text: &str, target: &str) -> f64;
+}
+
+// Heuristics that can be chosen by name in a `SearchConfig`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeuristicKind {
+    // Levenshtein with length penalties and an n-gram bonus
+    #[default]
+    Weighted,
+    Levenshtein,
+    Multiset,
+    Lcs,
+    // Uniform-cost search: states are ordered by path cost alone
+    Zero,
+}
+
+impl HeuristicKind {
+    pub fn build(self, weights: DistanceWeights) -> Box<dyn Heuristic> {
+        match self {
+            HeuristicKind::Weighted => Box::new(WeightedLevenshtein { weights }),
+            HeuristicKind::Levenshtein => Box::new(Levenshtein),
+            HeuristicKind::Multiset => Box::new(Multiset),
+            HeuristicKind::Lcs => Box::new(LongestCommonSubsequence),
+            HeuristicKind::Zero => Box::new(Zero),
+        }
+    }
+}
+
+impl std::str::FromStr for HeuristicKind {
+    type Err = String;
+    
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "weighted" => Ok(HeuristicKind::Weighted),
+            "levenshtein" => Ok(HeuristicKind::Levenshtein),
+            "multiset" => Ok(HeuristicKind::Multiset),
+            "lcs" => Ok(HeuristicKind::Lcs),
+            "zero" => Ok(HeuristicKind::Zero),
+            _ => Err(format!("Unknown heuristic: {}", name)),
+        }
+    }
+}
+
+// The engine's original distance, see `weighted_distance`
+pub struct WeightedLevenshtein {
+    pub weights: DistanceWeights,
+}
+
+impl Heuristic for WeightedLevenshtein {
+    fn distance(&self, text: &str, target: &str) -> f64 {
+        weighted_distance_with(text, target, &self.weights)
+    }
+}
+
+// Unit-cost insertions, deletions and substitutions
+pub struct Levenshtein;
+
+impl Heuristic for Levenshtein {
+    fn distance(&self, text: &str, target: &str) -> f64 {
+        let chars1: Vec<char> = text.chars().collect();
+        let chars2: Vec<char> = target.chars().collect();
+        levenshtein(&chars1, &chars2) as f64
+    }
+}
+
+// Characters in one text but not the other, ignoring order
+pub struct Multiset;
+
+impl Heuristic for Multiset {
+    fn distance(&self, text: &str, target: &str) -> f64 {
+        let mut counts: HashMap<char, i32> = HashMap::new();
+        for c in text.chars() {
+            *counts.entry(c).or_insert(0) += 1;
+        }
+        for c in target.chars() {
+            *counts.entry(c).or_insert(0) -= 1;
+        }
+        counts.values().map(|count| count.unsigned_abs()).sum::<u32>() as f64
+    }
+}
+
+// Characters outside the longest common subsequence of the two texts, which
+// is the distance with insertions and deletions only
+pub struct LongestCommonSubsequence;
+
+impl Heuristic for LongestCommonSubsequence {
+    fn distance(&self, text: &str, target: &str) -> f64 {
+        let chars1: Vec<char> = text.chars().collect();
+        let chars2: Vec<char> = target.chars().collect();
+        
+        let mut prev_row = vec![0u32; chars2.len() + 1];
+        let mut curr_row = vec![0u32; chars2.len() + 1];
+        for &c1 in &chars1 {
+            for (j, &c2) in chars2.iter().enumerate() {
+                curr_row[j + 1] = if c1 == c2 { prev_row[j] + 1 } else { prev_row[j + 1].max(curr_row[j]) };
+            }
+            std::mem::swap(&mut prev_row, &mut curr_row);
+        }
+        let common = prev_row[chars2.len()] as usize;
+        (chars1.len() + chars2.len() - 2 * common) as f64
+    }
+}
+
+pub struct Zero;
+
+impl Heuristic for Zero {
+    fn distance(&self, _text: &str, _target: &str) -> f64 {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+    use super::*;
+    use crate::config::SearchConfig;
+    use crate::engine::Pathfinder;
+    
+    #[test]
+    fn distances_between_kana_texts() {
+        assert_eq!(Levenshtein.distance("なぞとき", "なぞなぞ"), 2.0);
+        assert_eq!(Multiset.distance("ときなぞ", "なぞとき"), 0.0);
+        assert_eq!(Multiset.distance("なぞときき", "なぞと"), 2.0);
+        assert_eq!(LongestCommonSubsequence.distance("なかぞとき", "なぞとけ"), 3.0);
+        assert_eq!(Zero.distance("あ", "い"), 0.0);
+        assert_eq!(HeuristicKind::Weighted.build(DistanceWeights::default()).distance("なぞ", "なぞ"), 0.0);
+    }
+    
+    // Levenshtein that counts how often the engine asks for it
+    struct Counting(Arc<AtomicUsize>);
+    
+    impl Heuristic for Counting {
+        fn distance(&self, text: &str, target: &str) -> f64 {
+            self.0.fetch_add(1, AtomicOrdering::Relaxed);
+            Levenshtein.distance(text, target)
+        }
+    }
+    
+    #[test]
+    fn engines_use_a_heuristic_of_their_own() {
+        let hints = crate::hint::parse_hints(include_str!("../fixtures/hints.json")).unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let config = SearchConfig { max_depth: 8, ..SearchConfig::default() };
+        let mut engine = Pathfinder::with_heuristic("あかいとり", "かと", hints, config, Box::new(Counting(calls.clone()))).unwrap();
+        assert!(engine.run().found);
+        assert!(calls.load(AtomicOrdering::Relaxed) > 0);
+    }
+}
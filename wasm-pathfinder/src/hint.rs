@@ -0,0 +1,405 @@
+use std::borrow::Cow;
+use serde::{Serialize, Deserialize};
+
+use crate::error::Error;
+use crate::kana::{add_dakuten, add_handakuten, base_char, nfc, remove_marks};
+
+// Hint types matching TypeScript, plus the voicing mark operations
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HintOperation {
+    Remove { target: String },
+    Replace { target: String, replacement: String },
+    // Put a dakuten on the target's kana (か → が)
+    AddDakuten { target: String },
+    // Put a handakuten on the target's kana (は → ぱ)
+    AddHandakuten { target: String },
+    // Take the dakuten or handakuten off the target's kana (が → か), "点を取る"
+    RemoveMarks { target: String },
+}
+
+impl HintOperation {
+    pub fn target(&self) -> &str {
+        match self {
+            HintOperation::Remove { target }
+            | HintOperation::Replace { target, .. }
+            | HintOperation::AddDakuten { target }
+            | HintOperation::AddHandakuten { target }
+            | HintOperation::RemoveMarks { target } => target,
+        }
+    }
+    
+    // Text that takes the place of the target, in NFC; empty for removals
+    pub fn replacement(&self) -> Cow<'_, str> {
+        match self {
+            HintOperation::Remove { .. } => Cow::Borrowed(""),
+            HintOperation::Replace { replacement, .. } => nfc(replacement),
+            HintOperation::AddDakuten { target } => Cow::Owned(add_dakuten(target)),
+            HintOperation::AddHandakuten { target } => Cow::Owned(add_handakuten(target)),
+            HintOperation::RemoveMarks { target } => Cow::Owned(remove_marks(target)),
+        }
+    }
+    
+    // The same operation with its target and replacement passed through `f`
+    pub fn map_text(&self, f: impl Fn(&str) -> String) -> HintOperation {
+        match self {
+            HintOperation::Remove { target } => HintOperation::Remove { target: f(target) },
+            HintOperation::Replace { target, replacement } => HintOperation::Replace {
+                target: f(target),
+                replacement: f(replacement),
+            },
+            HintOperation::AddDakuten { target } => HintOperation::AddDakuten { target: f(target) },
+            HintOperation::AddHandakuten { target } => HintOperation::AddHandakuten { target: f(target) },
+            HintOperation::RemoveMarks { target } => HintOperation::RemoveMarks { target: f(target) },
+        }
+    }
+}
+
+// How familiar a hint is, from A (common in nazotoki) to D (a stretch)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HintGroup {
+    A,
+    B,
+    C,
+    D,
+}
+
+impl HintGroup {
+    // 0 for A up to 3 for D
+    pub fn rank(self) -> usize {
+        match self {
+            HintGroup::A => 0,
+            HintGroup::B => 1,
+            HintGroup::C => 2,
+            HintGroup::D => 3,
+        }
+    }
+    
+    // Search cost of a hint in this group, so obscure hints are used last
+    pub fn default_cost(self) -> f64 {
+        match self {
+            HintGroup::A => 1.0,
+            HintGroup::B => 1.5,
+            HintGroup::C => 2.0,
+            HintGroup::D => 3.0,
+        }
+    }
+}
+
+// Whether a hint rewrites every occurrence of its target at once or only one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OccurrenceMode {
+    #[default]
+    All,
+    Single,
+}
+
+impl std::str::FromStr for OccurrenceMode {
+    type Err = String;
+    
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "all" => Ok(OccurrenceMode::All),
+            "single" => Ok(OccurrenceMode::Single),
+            _ => Err(format!("Unknown occurrence mode: {}", mode)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hint {
+    pub name: String,
+    pub reading: String,
+    pub operation: HintOperation,
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<HintGroup>,
+    // Cost of one application; defaults to the group's cost, or 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    // Overrides the search's occurrence mode for this hint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occurrences: Option<OccurrenceMode>,
+    // Match the target whether or not its kana carry dakuten or handakuten
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_voicing: bool,
+}
+
+impl Hint {
+    pub fn cost(&self) -> f64 {
+        self.cost.unwrap_or_else(|| self.group.map_or(1.0, HintGroup::default_cost))
+    }
+    
+    // Apply the hint to a text, returning None if it changes nothing.
+    // Text and target are compared in NFC, so composed and decomposed kana
+    // match each other; the result is always composed.
+    pub fn apply(&self, text: &str) -> Option<String> {
+        let text = nfc(text);
+        let replacement = self.operation.replacement();
+        
+        let new_text = if self.ignore_voicing {
+            let chars: Vec<char> = text.chars().collect();
+            let target = self.target_chars();
+            let mut new_text = String::with_capacity(text.len());
+            let mut i = 0;
+            while i < chars.len() {
+                if self.matches_at(&chars, &target, i) {
+                    new_text.push_str(&replacement);
+                    i += target.len();
+                } else {
+                    new_text.push(chars[i]);
+                    i += 1;
+                }
+            }
+            new_text
+        } else {
+            text.replace(&*nfc(self.operation.target()), &replacement)
+        };
+        
+        if new_text != *text {
+            Some(new_text)
+        } else {
+            None
+        }
+    }
+    
+    // Texts one application of the hint can give, following its own
+    // occurrence mode: `apply` for all occurrences, or one text per
+    // occurrence that gives a different one
+    pub fn applications(&self, text: &str) -> Vec<String> {
+        match self.occurrences.unwrap_or_default() {
+            OccurrenceMode::All => self.apply(text).into_iter().collect(),
+            OccurrenceMode::Single => {
+                let mut texts: Vec<String> = Vec::new();
+                for position in self.occurrences_in(text) {
+                    if let Some(new_text) = self.apply_at(text, position) {
+                        if !texts.contains(&new_text) {
+                            texts.push(new_text);
+                        }
+                    }
+                }
+                texts
+            }
+        }
+    }
+    
+    // Character positions where the target starts, overlapping matches included
+    pub fn occurrences_in(&self, text: &str) -> Vec<usize> {
+        let chars: Vec<char> = nfc(text).chars().collect();
+        let target = self.target_chars();
+        (0..chars.len())
+            .filter(|&position| self.matches_at(&chars, &target, position))
+            .collect()
+    }
+    
+    // Positions `apply` rewrites: the leftmost occurrences that do not overlap
+    pub fn match_positions(&self, text: &str) -> Vec<usize> {
+        let target_len = self.target_chars().len();
+        let mut positions: Vec<usize> = Vec::new();
+        for position in self.occurrences_in(text) {
+            if positions.last().is_none_or(|&last| position >= last + target_len) {
+                positions.push(position);
+            }
+        }
+        positions
+    }
+    
+    // Apply the hint to the single occurrence starting at character `position`
+    pub fn apply_at(&self, text: &str, position: usize) -> Option<String> {
+        let chars: Vec<char> = nfc(text).chars().collect();
+        let target = self.target_chars();
+        if !self.matches_at(&chars, &target, position) {
+            return None;
+        }
+        
+        let mut new_text: String = chars[..position].iter().collect();
+        new_text.push_str(&self.operation.replacement());
+        new_text.extend(&chars[position + target.len()..]);
+        Some(new_text)
+    }
+    
+    // The target in NFC, the form every match position refers to
+    pub(crate) fn target_chars(&self) -> Vec<char> {
+        nfc(self.operation.target()).chars().collect()
+    }
+    
+    // Whether the target occurs at `position`, ignoring voicing marks if asked to
+    fn matches_at(&self, chars: &[char], target: &[char], position: usize) -> bool {
+        match chars.get(position..position + target.len()) {
+            Some(window) if self.ignore_voicing => window.iter().zip(target)
+                .all(|(&a, &b)| base_char(a) == base_char(b)),
+            Some(window) => window == target,
+            None => false,
+        }
+    }
+}
+
+// Parse the hints JSON, rejecting malformed hints with an error naming the hint
+pub fn parse_hints(hints_json: &str) -> Result<Vec<Hint>, Error> {
+    let values: Vec<serde_json::Value> = serde_json::from_str(hints_json)
+        .map_err(|e| Error::InvalidHints(format!("Failed to parse hints: {}", e)))?;
+    
+    let hints = values.into_iter().enumerate().map(|(index, value)| {
+        let label = hint_label(index, &value);
+        serde_json::from_value(value)
+            .map_err(|e| Error::InvalidHints(format!("Invalid {}: {}", label, e)))
+    }).collect::<Result<Vec<Hint>, Error>>()?;
+    
+    validate_hints(&hints)?;
+    Ok(hints)
+}
+
+// Reject hints that would match everywhere or nowhere
+pub fn validate_hints(hints: &[Hint]) -> Result<(), Error> {
+    for (index, hint) in hints.iter().enumerate() {
+        if hint.operation.target().is_empty() {
+            return Err(Error::InvalidHints(format!(
+                "Invalid hint #{} \"{}\": operation target must not be empty",
+                index,
+                hint.name
+            )));
+        }
+        if !(hint.cost() > 0.0 && hint.cost().is_finite()) {
+            return Err(Error::InvalidHints(format!(
+                "Invalid hint #{} \"{}\": cost must be a positive number",
+                index,
+                hint.name
+            )));
+        }
+        if *hint.operation.replacement() == *nfc(hint.operation.target()) {
+            return Err(Error::InvalidHints(format!(
+                "Invalid hint #{} \"{}\": operation does not change its target",
+                index,
+                hint.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Hint with only a name and an operation, for tests
+#[cfg(test)]
+impl Hint {
+    pub(crate) fn new(name: &str, operation: HintOperation) -> Hint {
+        Hint {
+            name: name.to_string(),
+            reading: String::new(),
+            operation,
+            description: String::new(),
+            group: None,
+            cost: None,
+            occurrences: None,
+            ignore_voicing: false,
+        }
+    }
+}
+
+#[cfg(test)]
+impl HintOperation {
+    pub(crate) fn remove(target: &str) -> HintOperation {
+        HintOperation::Remove { target: target.to_string() }
+    }
+    
+    pub(crate) fn replace(target: &str, replacement: &str) -> HintOperation {
+        HintOperation::Replace { target: target.to_string(), replacement: replacement.to_string() }
+    }
+}
+
+fn hint_label(index: usize, value: &serde_json::Value) -> String {
+    match value.get("name").and_then(|name| name.as_str()) {
+        Some(name) => format!("hint #{} \"{}\"", index, name),
+        None => format!("hint #{}", index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn hint(operation: HintOperation, ignore_voicing: bool) -> Hint {
+        Hint { ignore_voicing, ..Hint::new("test", operation) }
+    }
+    
+    #[test]
+    fn composed_and_decomposed_kana_match_each_other() {
+        // Decomposed text, composed target
+        assert_eq!(hint(HintOperation::remove("が"), false).apply("か\u{3099}っこう").as_deref(), Some("っこう"));
+        // Composed text, decomposed target
+        assert_eq!(hint(HintOperation::remove("は\u{309A}"), false).apply("ぱん").as_deref(), Some("ん"));
+        // Untouched decomposed text is not a change
+        assert_eq!(hint(HintOperation::remove("こ"), false).apply("か\u{3099}"), None);
+    }
+    
+    #[test]
+    fn voicing_mark_operations() {
+        let dakuten = hint(HintOperation::AddDakuten { target: "か".to_string() }, false);
+        assert_eq!(dakuten.apply("はかま").as_deref(), Some("はがま"));
+        
+        let handakuten = hint(HintOperation::AddHandakuten { target: "は".to_string() }, false);
+        assert_eq!(handakuten.apply("はんこ").as_deref(), Some("ぱんこ"));
+        
+        // 点を取る, on composed and decomposed input
+        let unmark = hint(HintOperation::RemoveMarks { target: "が".to_string() }, false);
+        assert_eq!(unmark.apply("がっこう").as_deref(), Some("かっこう"));
+        assert_eq!(unmark.apply("か\u{3099}っこう").as_deref(), Some("かっこう"));
+        assert_eq!(unmark.apply("かっこう"), None);
+    }
+    
+    #[test]
+    fn ignore_voicing_matches_marked_and_unmarked_targets() {
+        let remove_ka = hint(HintOperation::remove("か"), true);
+        assert_eq!(remove_ka.apply("かがみ").as_deref(), Some("み"));
+        assert_eq!(remove_ka.apply("か\u{3099}み").as_deref(), Some("み"));
+        assert_eq!(remove_ka.occurrences_in("かがみ"), vec![0, 1]);
+        assert_eq!(remove_ka.apply_at("かがみ", 1).as_deref(), Some("かみ"));
+        
+        // Without the option only the exact kana matches
+        assert_eq!(hint(HintOperation::remove("か"), false).apply("かがみ").as_deref(), Some("がみ"));
+    }
+    
+    #[test]
+    fn single_occurrence_hints_give_one_text_per_occurrence() {
+        let mut remove_ko = hint(HintOperation::remove("こ"), false);
+        assert_eq!(remove_ko.applications("こあこ"), vec!["あ"]);
+        remove_ko.occurrences = Some(OccurrenceMode::Single);
+        assert_eq!(remove_ko.applications("こあこ"), vec!["あこ", "こあ"]);
+        assert_eq!(remove_ko.applications("ここ"), vec!["こ"]);
+        assert!(remove_ko.applications("あ").is_empty());
+    }
+    
+    #[test]
+    fn rejects_marks_that_cannot_be_added() {
+        let hints = vec![hint(HintOperation::AddDakuten { target: "あ".to_string() }, false)];
+        assert!(validate_hints(&hints).is_err());
+    }
+    
+    // Message of the error for a single hint with the given operation and extras
+    fn parse_error(operation: &str, extra: &str) -> String {
+        let json = format!(r#"[{{"name": "狸", "reading": "たぬき", "operation": {}, "description": ""{}}}]"#, operation, extra);
+        match parse_hints(&json) {
+            Err(Error::InvalidHints(message)) => message,
+            other => panic!("expected invalid hints, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn parse_errors_name_the_hint() {
+        assert!(parse_error(r#"{"type": "swap", "target": "た"}"#, "").contains("unknown variant `swap`"));
+        
+        let message = parse_error(r#"{"type": "replace", "target": "た"}"#, "");
+        assert!(message.contains("hint #0 \"狸\""), "{}", message);
+        assert!(message.contains("replacement"), "{}", message);
+        
+        let message = parse_error(r#"{"type": "remove", "target": ""}"#, "");
+        assert_eq!(message, "Invalid hint #0 \"狸\": operation target must not be empty");
+        
+        for cost in ["0", "-1.5"] {
+            let message = parse_error(r#"{"type": "remove", "target": "た"}"#, &format!(r#", "cost": {}"#, cost));
+            assert_eq!(message, "Invalid hint #0 \"狸\": cost must be a positive number");
+        }
+        
+        let json = r#"[{"name": "狸", "reading": "たぬき", "operation": {"type": "remove", "target": "た"}, "description": "", "cost": 0.5}]"#;
+        assert_eq!(parse_hints(json).unwrap()[0].cost(), 0.5);
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Compact id of an interned text
+pub(crate) type TextId = u32;
+
+// Every distinct search text stored once. The map and the id table share
+// each allocation, and states, visited sets and caches refer to texts by id.
+#[derive(Default)]
+pub(crate) struct Interner {
+    ids: HashMap<Arc<str>, TextId>,
+    texts: Vec<Arc<str>>,
+}
+
+impl Interner {
+    pub(crate) fn from_texts(texts: Vec<String>) -> Interner {
+        let mut interner = Interner::default();
+        for text in texts {
+            interner.intern(&text);
+        }
+        interner
+    }
+    
+    pub(crate) fn intern(&mut self, text: &str) -> TextId {
+        if let Some(&id) = self.ids.get(text) {
+            return id;
+        }
+        let id = TextId::try_from(self.texts.len()).expect("fewer than 2^32 distinct texts");
+        let text: Arc<str> = Arc::from(text);
+        self.ids.insert(text.clone(), id);
+        self.texts.push(text);
+        id
+    }
+    
+    // Id of a text that was interned before, without interning it
+    pub(crate) fn lookup(&self, text: &str) -> Option<TextId> {
+        self.ids.get(text).copied()
+    }
+    
+    // Shared handle, so the text can be used while the owner is borrowed mutably
+    pub(crate) fn get(&self, id: TextId) -> Arc<str> {
+        self.texts[id as usize].clone()
+    }
+    
+    pub(crate) fn len(&self) -> usize {
+        self.texts.len()
+    }
+    
+    // Texts in id order, for snapshots
+    pub(crate) fn texts(&self) -> Vec<String> {
+        self.texts.iter().map(|text| text.to_string()).collect()
+    }
+    
+    // Forget every text that `live` rejects and renumber the rest, returning
+    // the new id of each old id (None for forgotten texts)
+    pub(crate) fn retain(&mut self, live: impl Fn(TextId) -> bool) -> Vec<Option<TextId>> {
+        let old_texts = std::mem::take(&mut self.texts);
+        self.ids.clear();
+        
+        let mut new_ids = Vec::with_capacity(old_texts.len());
+        for (old_id, text) in old_texts.into_iter().enumerate() {
+            if live(old_id as TextId) {
+                let id = self.texts.len() as TextId;
+                self.ids.insert(text.clone(), id);
+                self.texts.push(text);
+                new_ids.push(Some(id));
+            } else {
+                new_ids.push(None);
+            }
+        }
+        new_ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn retain_renumbers_the_texts_it_keeps() {
+        let mut interner = Interner::default();
+        let a = interner.intern("なぞ");
+        let b = interner.intern("とき");
+        assert_eq!(interner.intern("なぞ"), a);
+        
+        let new_ids = interner.retain(|id| id == b);
+        assert_eq!(new_ids, vec![None, Some(0)]);
+        assert_eq!(interner.lookup("なぞ"), None);
+        assert_eq!(&*interner.get(0), "とき");
+    }
+}
@@ -0,0 +1,196 @@
+use std::borrow::Cow;
+use serde::{Serialize, Deserialize};
+use unicode_normalization::char::{compose, decompose_canonical};
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+// Combining voicing marks, as found in decomposed (NFD) kana
+pub const DAKUTEN: char = '\u{3099}';
+pub const HANDAKUTEN: char = '\u{309A}';
+
+// NFC form of a text, borrowing it when it is already composed
+pub fn nfc(text: &str) -> Cow<'_, str> {
+    if is_nfc_quick(text.chars()) == IsNormalized::Yes {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(text.nfc().collect())
+    }
+}
+
+// Kana without its dakuten or handakuten; other characters are unchanged
+pub fn base_char(c: char) -> char {
+    let mut parts = Vec::with_capacity(2);
+    decompose_canonical(c, |part| parts.push(part));
+    match parts.as_slice() {
+        [base, DAKUTEN] | [base, HANDAKUTEN] => *base,
+        _ => c,
+    }
+}
+
+// Every form of a character with and without voicing marks
+pub fn voicing_variants(c: char) -> Vec<char> {
+    let base = base_char(c);
+    let mut variants = vec![base];
+    variants.extend(compose(base, DAKUTEN));
+    variants.extend(compose(base, HANDAKUTEN));
+    variants
+}
+
+// Add a dakuten to every character that has a voiced form (か → が)
+pub fn add_dakuten(text: &str) -> String {
+    nfc(text).chars().map(|c| compose(base_char(c), DAKUTEN).unwrap_or(c)).collect()
+}
+
+// Add a handakuten to every character that has one (は → ぱ)
+pub fn add_handakuten(text: &str) -> String {
+    nfc(text).chars().map(|c| compose(base_char(c), HANDAKUTEN).unwrap_or(c)).collect()
+}
+
+// Take the dakuten and handakuten off every character (が → か, ぱ → は)
+pub fn remove_marks(text: &str) -> String {
+    nfc(text).chars().map(base_char).collect()
+}
+
+// Which differences in how kana are written the engine should ignore
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Normalization {
+    // カタカナ → ひらがな
+    pub katakana_to_hiragana: bool,
+    // ｶﾀｶﾅ → カタカナ, including half-width voicing marks
+    pub half_width_to_full_width: bool,
+    // Compose decomposed kana (か + ゙ → が), also from spacing marks (か゛)
+    pub nfc: bool,
+    // ゃ → や, っ → つ; off by default because small kana usually read differently
+    pub small_kana_to_full_size: bool,
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Normalization {
+            katakana_to_hiragana: true,
+            half_width_to_full_width: true,
+            nfc: true,
+            small_kana_to_full_size: false,
+        }
+    }
+}
+
+impl Normalization {
+    // Leave every text exactly as written
+    pub fn none() -> Self {
+        Normalization {
+            katakana_to_hiragana: false,
+            half_width_to_full_width: false,
+            nfc: false,
+            small_kana_to_full_size: false,
+        }
+    }
+    
+    pub fn apply(&self, text: &str) -> String {
+        self.clusters(text).into_iter().map(|(normalized, _)| normalized).collect()
+    }
+    
+    // Split `text` into clusters of a character and the voicing marks that
+    // follow it, returning each cluster normalized together with its
+    // original form, so normalized text can be mapped back to what was typed
+    pub fn clusters<'a>(&self, text: &'a str) -> Vec<(String, &'a str)> {
+        let mut starts: Vec<usize> = text.char_indices()
+            .filter(|(i, c)| *i == 0 || !is_voicing_mark(*c))
+            .map(|(i, _)| i)
+            .collect();
+        starts.push(text.len());
+        
+        starts.windows(2)
+            .map(|bounds| {
+                let cluster = &text[bounds[0]..bounds[1]];
+                (self.normalize_cluster(cluster), cluster)
+            })
+            .collect()
+    }
+    
+    fn normalize_cluster(&self, cluster: &str) -> String {
+        let mut text: String = if self.half_width_to_full_width {
+            cluster.chars()
+                .flat_map(|c| {
+                    let half_width = ('\u{FF61}'..='\u{FF9F}').contains(&c);
+                    let full: Vec<char> = if half_width { std::iter::once(c).nfkc().collect() } else { vec![c] };
+                    full
+                })
+                .collect()
+        } else {
+            cluster.to_string()
+        };
+        if self.nfc {
+            // Spacing marks typed after a kana (ソ゛) count as combining ones
+            let combined: String = text.chars().enumerate()
+                .map(|(i, c)| match c {
+                    '\u{309B}' if i > 0 => DAKUTEN,
+                    '\u{309C}' if i > 0 => HANDAKUTEN,
+                    _ => c,
+                })
+                .collect();
+            text = nfc(&combined).into_owned();
+        }
+        if self.katakana_to_hiragana {
+            text = text.chars().map(katakana_to_hiragana).collect();
+        }
+        if self.small_kana_to_full_size {
+            text = text.chars().map(small_to_full_size).collect();
+        }
+        text
+    }
+}
+
+// Combining, spacing or half-width dakuten and handakuten
+fn is_voicing_mark(c: char) -> bool {
+    matches!(c, DAKUTEN | HANDAKUTEN | '\u{309B}' | '\u{309C}' | '\u{FF9E}' | '\u{FF9F}')
+}
+
+// ァ..ヶ and ヽヾ sit exactly 0x60 above their hiragana
+fn katakana_to_hiragana(c: char) -> char {
+    match c {
+        '\u{30A1}'..='\u{30F6}' | '\u{30FD}' | '\u{30FE}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+fn small_to_full_size(c: char) -> char {
+    match c {
+        'ぁ' => 'あ',
+        'ぃ' => 'い',
+        'ぅ' => 'う',
+        'ぇ' => 'え',
+        'ぉ' => 'お',
+        'っ' => 'つ',
+        'ゃ' => 'や',
+        'ゅ' => 'ゆ',
+        'ょ' => 'よ',
+        'ゎ' => 'わ',
+        'ゕ' => 'か',
+        'ゖ' => 'け',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn normalizes_kana_variants_to_composed_hiragana() {
+        let normalization = Normalization::default();
+        assert_eq!(normalization.apply("ﾅｿﾞﾄｷ"), "なぞとき");
+        assert_eq!(normalization.apply("ナソ\u{3099}トキ"), "なぞとき");
+        assert_eq!(normalization.apply("ナソ゛トキ"), "なぞとき");
+        assert_eq!(normalization.apply("がっこう"), "がっこう");
+        
+        let small_kana = Normalization { small_kana_to_full_size: true, ..Normalization::default() };
+        assert_eq!(small_kana.apply("ガッコウ"), "がつこう");
+    }
+    
+    #[test]
+    fn clusters_keep_the_typed_form() {
+        let clusters = Normalization::default().clusters("ｿﾞこ");
+        assert_eq!(clusters, vec![("ぞ".to_string(), "ｿﾞ"), ("こ".to_string(), "こ")]);
+    }
+}
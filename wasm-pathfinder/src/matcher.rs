@@ -0,0 +1,165 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::hint::Hint;
+use crate::kana::nfc;
+
+const ROOT: u32 = 0;
+
+// Aho–Corasick automaton over the hint targets, so a single pass over a text
+// finds every hint that can apply to it instead of one search per hint
+#[derive(Debug)]
+pub(crate) struct HintMatcher {
+    // Trie transitions of each state
+    goto: Vec<HashMap<char, u32>>,
+    // Longest proper suffix of a state that is also a trie state
+    fail: Vec<u32>,
+    // Targets that end at a state, including those of its suffixes
+    outputs: Vec<Vec<u32>>,
+    // Target of each hint, or None for hints that ignore voicing marks
+    hint_targets: Vec<Option<u32>>,
+    target_count: usize,
+}
+
+impl HintMatcher {
+    pub(crate) fn new(hints: &[Hint]) -> Self {
+        let mut matcher = HintMatcher {
+            goto: vec![HashMap::new()],
+            fail: vec![ROOT],
+            outputs: vec![Vec::new()],
+            hint_targets: Vec::with_capacity(hints.len()),
+            target_count: 0,
+        };
+        
+        // Hints sharing a target share one pattern. Targets are matched in
+        // NFC, like `Hint::apply` does. A hint that ignores voicing marks can
+        // match without its exact target, so those are always tried.
+        let mut targets: HashMap<String, u32> = HashMap::new();
+        for hint in hints {
+            if hint.ignore_voicing {
+                matcher.hint_targets.push(None);
+                continue;
+            }
+            let target = nfc(hint.operation.target()).into_owned();
+            let next_id = targets.len() as u32;
+            let id = *targets.entry(target).or_insert_with_key(|target| {
+                matcher.insert(target, next_id);
+                next_id
+            });
+            matcher.hint_targets.push(Some(id));
+        }
+        matcher.target_count = targets.len();
+        
+        matcher.link();
+        matcher
+    }
+    
+    fn insert(&mut self, target: &str, id: u32) {
+        let mut state = ROOT;
+        for c in target.chars() {
+            state = match self.goto[state as usize].get(&c) {
+                Some(&next) => next,
+                None => {
+                    let next = self.goto.len() as u32;
+                    self.goto.push(HashMap::new());
+                    self.fail.push(ROOT);
+                    self.outputs.push(Vec::new());
+                    self.goto[state as usize].insert(c, next);
+                    next
+                }
+            };
+        }
+        self.outputs[state as usize].push(id);
+    }
+    
+    // Breadth-first, so every shorter state has its failure link before the
+    // states below it need it
+    fn link(&mut self) {
+        let mut queue: VecDeque<u32> = self.goto[ROOT as usize].values().copied().collect();
+        while let Some(state) = queue.pop_front() {
+            let children: Vec<(char, u32)> = self.goto[state as usize].iter()
+                .map(|(&c, &child)| (c, child))
+                .collect();
+            for (c, child) in children {
+                let mut fallback = self.fail[state as usize];
+                let fail = loop {
+                    if let Some(&next) = self.goto[fallback as usize].get(&c) {
+                        break next;
+                    }
+                    if fallback == ROOT {
+                        break ROOT;
+                    }
+                    fallback = self.fail[fallback as usize];
+                };
+                self.fail[child as usize] = fail;
+                let inherited = self.outputs[fail as usize].clone();
+                self.outputs[child as usize].extend(inherited);
+                queue.push_back(child);
+            }
+        }
+    }
+    
+    fn next_state(&self, mut state: u32, c: char) -> u32 {
+        loop {
+            if let Some(&next) = self.goto[state as usize].get(&c) {
+                return next;
+            }
+            if state == ROOT {
+                return ROOT;
+            }
+            state = self.fail[state as usize];
+        }
+    }
+    
+    // Indices of the hints whose target occurs in `text`, in hint order; the
+    // same hints as comparing the NFC forms of text and target for each of them
+    pub(crate) fn candidates(&self, text: &str) -> Vec<usize> {
+        let mut found = vec![false; self.target_count];
+        let mut state = ROOT;
+        for c in nfc(text).chars() {
+            state = self.next_state(state, c);
+            for &id in &self.outputs[state as usize] {
+                found[id as usize] = true;
+            }
+        }
+        
+        self.hint_targets.iter().enumerate()
+            .filter(|(_, target)| target.is_none_or(|id| found[id as usize]))
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hint::HintOperation;
+    
+    const HINTS: &str = include_str!("../fixtures/hints.json");
+    
+    #[test]
+    fn finds_the_same_hints_as_checking_each_target() {
+        let hints: Vec<Hint> = serde_json::from_str(HINTS).unwrap();
+        let matcher = HintMatcher::new(&hints);
+        for text in ["たなぞのときこけしのた", "あかいとり", "なぞなぞ", "", "ぬ"] {
+            let expected: Vec<usize> = (0..hints.len())
+                .filter(|&index| hints[index].ignore_voicing || text.contains(hints[index].operation.target()))
+                .collect();
+            assert_eq!(matcher.candidates(text), expected, "{}", text);
+        }
+    }
+    
+    #[test]
+    fn overlapping_targets_are_all_found() {
+        let hints = ["とき", "なぞとき", "ぞと", "きな"].map(|target| Hint::new(target, HintOperation::remove(target)));
+        let matcher = HintMatcher::new(&hints);
+        assert_eq!(matcher.candidates("なぞとき"), vec![0, 1, 2]);
+        assert_eq!(matcher.candidates("ときなぞ"), vec![0, 3]);
+    }
+    
+    #[test]
+    fn composed_and_decomposed_targets_match_each_other() {
+        let matcher = HintMatcher::new(&["か\u{3099}", "ぱ"].map(|target| Hint::new(target, HintOperation::remove(target))));
+        assert_eq!(matcher.candidates("こうが"), vec![0]);
+        assert_eq!(matcher.candidates("は\u{309A}ん"), vec![1]);
+    }
+}
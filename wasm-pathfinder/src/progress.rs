@@ -0,0 +1,83 @@
+// Estimate the total search space based on hints and depth
+pub(crate) fn estimate_search_space(hints_count: usize, max_depth: usize) -> usize {
+    // More realistic estimation based on observed patterns:
+    // - Most hints only apply to specific patterns
+    // - Visited state pruning is very effective
+    // - Branching factor decreases with depth
+    
+    if hints_count == 0 || max_depth == 0 {
+        return 1;
+    }
+    
+    // Start with a much lower branching factor
+    // In practice, only 10-20% of hints apply to any given state
+    let initial_branching = (hints_count as f64 * 0.15).max(1.0);
+    
+    // Branching factor decreases with depth due to:
+    // - More states being already visited
+    // - Convergence toward the target
+    let mut total = 1.0;
+    let mut states_at_depth = 1.0;
+    let mut current_branching = initial_branching;
+    
+    for _depth in 1..=max_depth {
+        // Reduce branching factor as we go deeper
+        // This models the convergence effect
+        current_branching *= 0.8;
+        current_branching = current_branching.max(0.5);
+        
+        states_at_depth *= current_branching;
+        total += states_at_depth;
+        
+        // Cap the growth to prevent unrealistic estimates
+        if states_at_depth > 100000.0 {
+            // If we're estimating more than 100k states at a single depth,
+            // we're probably overestimating
+            break;
+        }
+    }
+    
+    // Much smaller buffer since our estimate is more conservative
+    (total * 1.1).min(1000000.0) as usize // Cap at 1 million max
+}
+
+// Calculate progress percentage
+pub(crate) fn calculate_progress_percentage(
+    states_explored: usize,
+    queue_size: usize,
+    estimated_total: usize
+) -> f64 {
+    // If no states left to explore, we're essentially done
+    if queue_size == 0 && states_explored > 0 {
+        return 99.9; // Not 100% to avoid confusion before final result
+    }
+    
+    if estimated_total == 0 {
+        return 0.0;
+    }
+    
+    // Consider both explored states and remaining queue
+    let effective_progress = states_explored;
+    let mut percentage = effective_progress as f64 / estimated_total as f64 * 100.0;
+    
+    // Adaptive progress: if we've explored more than our estimate,
+    // scale the percentage based on queue size
+    if states_explored > estimated_total {
+        // We underestimated - use queue size as indicator
+        if queue_size < 100 {
+            percentage = 90.0 + (100.0 - queue_size as f64) * 0.099;
+        } else {
+            percentage = 50.0 + (states_explored as f64 / (states_explored + queue_size) as f64) * 40.0;
+        }
+    }
+    
+    // Ensure reasonable bounds
+    percentage = percentage.clamp(0.1, 99.9);
+    
+    // Show at least 1% after exploring reasonable number of states
+    if percentage < 1.0 && states_explored > 10 {
+        return 1.0;
+    }
+    
+    percentage
+}
@@ -0,0 +1,144 @@
+use std::cmp::Ordering;
+use serde::{Serialize, Deserialize};
+
+use crate::dictionary::Dictionary;
+use crate::hint::Hint;
+use crate::kana::Normalization;
+use crate::verify::apply_in_every_order;
+
+// Plausibility weights
+const WORD_SCORE: f64 = 2.0;
+const SEGMENTED_SCORE: f64 = 1.0;
+const ORDERINGS_WEIGHT: f64 = 0.25;
+const CHAR_SCORE: f64 = 0.25;
+// Answers shorter than this many characters score lower
+const MIN_ANSWER_CHARS: usize = 4;
+
+// A possible answer with the evidence behind its score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candidate {
+    pub text: String,
+    pub score: f64,
+    // The whole text is a dictionary word
+    pub in_dictionary: bool,
+    // The text splits into dictionary words
+    pub segmentable: bool,
+    // Application orders that produce this text, and one of them
+    pub orderings: u64,
+    pub path: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolveResult {
+    // Best candidate first
+    pub candidates: Vec<Candidate>,
+    // Orders that got stuck because some hint no longer changed the text
+    pub blocked_orderings: u64,
+}
+
+// Solve a puzzle without knowing its answer: apply every hint exactly once,
+// in every order, and rank the texts that come out.
+//
+// Without a dictionary the ranking only rewards texts that many orders agree
+// on and that are not too short; with one, real words and texts made of
+// words come first.
+pub fn solve(question: &str, hints: &[Hint], dictionary: Option<&Dictionary>) -> SolveResult {
+    let normalization = Normalization::default();
+    let question = normalization.apply(question);
+    let hints: Vec<Hint> = hints.iter()
+        .map(|hint| {
+            let mut hint = hint.clone();
+            hint.operation = hint.operation.map_text(|text| normalization.apply(text));
+            hint
+        })
+        .collect();
+    
+    let (terminals, blocked_orderings) = apply_in_every_order(&question, &hints);
+    
+    let mut candidates: Vec<Candidate> = terminals.into_iter()
+        .map(|terminal| {
+            let in_dictionary = dictionary.is_some_and(|d| d.contains(&terminal.text));
+            let segmentable = dictionary.is_some_and(|d| d.accepts(&terminal.text));
+            let chars = terminal.text.chars().count();
+            
+            let mut score = (terminal.orderings as f64).ln() * ORDERINGS_WEIGHT
+                + chars.min(MIN_ANSWER_CHARS) as f64 * CHAR_SCORE;
+            if in_dictionary {
+                score += WORD_SCORE;
+            } else if segmentable {
+                score += SEGMENTED_SCORE;
+            }
+            
+            Candidate {
+                text: terminal.text,
+                score,
+                in_dictionary,
+                segmentable,
+                orderings: terminal.orderings,
+                path: terminal.path,
+            }
+        })
+        .collect();
+    
+    candidates.sort_by(|a, b| {
+        b.score.partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.text.cmp(&b.text))
+    });
+    
+    SolveResult {
+        candidates,
+        blocked_orderings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hint::{HintOperation, OccurrenceMode};
+    
+    // かき gives くく when か→き goes first and きく otherwise, one order each
+    fn hints() -> Vec<Hint> {
+        vec![Hint::new("p", HintOperation::replace("か", "き")), Hint::new("q", HintOperation::replace("き", "く"))]
+    }
+    
+    fn ranking(words: Option<&str>) -> Vec<String> {
+        let dictionary = words.map(Dictionary::from_word_list);
+        solve("かき", &hints(), dictionary.as_ref()).candidates.into_iter()
+            .map(|candidate| candidate.text)
+            .collect()
+    }
+    
+    #[test]
+    fn words_rank_above_segmentable_texts_and_those_above_the_rest() {
+        // Equal scores fall back to text order
+        assert_eq!(ranking(None), vec!["きく", "くく"]);
+        // A word beats a text split into words
+        assert_eq!(ranking(Some("きく\nく")), vec!["きく", "くく"]);
+        assert_eq!(ranking(Some("くく\nき\nく")), vec!["くく", "きく"]);
+        // A text split into words beats one that cannot be
+        assert_eq!(ranking(Some("く")), vec!["くく", "きく"]);
+        
+        let result = solve("かき", &hints(), Some(&Dictionary::from_word_list("くく\nき\nく")));
+        let flags: Vec<(bool, bool)> = result.candidates.iter().map(|c| (c.in_dictionary, c.segmentable)).collect();
+        assert_eq!(flags, vec![(true, true), (false, true)]);
+    }
+    
+    #[test]
+    fn single_occurrence_hints_are_applied_once() {
+        let remove_ko = Hint { occurrences: Some(OccurrenceMode::Single), ..Hint::new("こ", HintOperation::remove("こ")) };
+        let result = solve("こあこ", &[remove_ko.clone(), remove_ko], None);
+        let texts: Vec<&str> = result.candidates.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, vec!["あ"]);
+    }
+    
+    #[test]
+    fn question_and_hints_are_normalized() {
+        // Half-width and full-width katakana in the question and the hints
+        let hints = vec![Hint::new("p", HintOperation::replace("カ", "キ")), Hint::new("q", HintOperation::replace("き", "ｸ"))];
+        let result = solve("ｶキ", &hints, None);
+        let texts: Vec<&str> = result.candidates.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, vec!["きく", "くく"]);
+        assert_eq!(result.blocked_orderings, 0);
+    }
+}
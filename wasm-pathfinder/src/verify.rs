@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+use crate::hint::Hint;
+
+// A string left once every hint has been applied, with how many application
+// orders reach it and one example order. Single-occurrence hints count each
+// occurrence they can rewrite as a separate order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Terminal {
+    pub text: String,
+    pub orderings: u64,
+    pub path: Vec<String>,
+}
+
+// Outcome of trying every application order of a hint multiset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniquenessReport {
+    pub terminals: Vec<Terminal>,
+    // Orders that got stuck because some hint no longer changed the text
+    pub blocked_orderings: u64,
+    pub answer_reachable: bool,
+    pub unique: bool,
+}
+
+// Partial application: how many orders reach it and one of them
+struct Partial {
+    orderings: u64,
+    path: Vec<usize>,
+}
+
+// Apply the hints to the question in every possible order and report the
+// strings that come out
+pub fn verify_uniqueness(question: &str, hints: &[Hint], answer: &str) -> UniquenessReport {
+    let (terminals, blocked_orderings) = apply_in_every_order(question, hints);
+    let answer_reachable = terminals.iter().any(|t| t.text == answer);
+    let unique = answer_reachable && terminals.len() == 1;
+    
+    UniquenessReport {
+        terminals,
+        blocked_orderings,
+        answer_reachable,
+        unique,
+    }
+}
+
+// Terminal strings of every order that applies each hint once, most common
+// first, and the number of orders that got stuck.
+//
+// Hints with the same operation are interchangeable, so the multiset is
+// reduced to distinct operations with counts and orders are counted over
+// those. Each layer applies one more hint; states are keyed by the text and
+// the remaining counts, so orders that converge are only expanded once.
+pub(crate) fn apply_in_every_order(question: &str, hints: &[Hint]) -> (Vec<Terminal>, u64) {
+    // Group identical operations, keeping the first hint of each as
+    // representative; how a hint matches its target is part of its operation
+    let mut distinct: Vec<usize> = Vec::new();
+    let mut counts: Vec<u8> = Vec::new();
+    for (index, hint) in hints.iter().enumerate() {
+        let existing = distinct.iter().position(|&d| {
+            hints[d].operation.target() == hint.operation.target()
+                && hints[d].operation.replacement() == hint.operation.replacement()
+                && hints[d].ignore_voicing == hint.ignore_voicing
+                && hints[d].occurrences == hint.occurrences
+        });
+        match existing {
+            Some(position) => counts[position] += 1,
+            None => {
+                distinct.push(index);
+                counts.push(1);
+            }
+        }
+    }
+    
+    let mut layer: HashMap<(String, Vec<u8>), Partial> = HashMap::new();
+    layer.insert((question.to_string(), counts), Partial { orderings: 1, path: Vec::new() });
+    let mut blocked_orderings: u64 = 0;
+    
+    for _ in 0..hints.len() {
+        let mut next_layer: HashMap<(String, Vec<u8>), Partial> = HashMap::new();
+        
+        for ((text, remaining), partial) in layer {
+            for (slot, &count) in remaining.iter().enumerate() {
+                if count == 0 {
+                    continue;
+                }
+                
+                let new_texts = hints[distinct[slot]].applications(&text);
+                if new_texts.is_empty() {
+                    blocked_orderings = blocked_orderings.saturating_add(partial.orderings);
+                    continue;
+                }
+                
+                let mut new_remaining = remaining.clone();
+                new_remaining[slot] -= 1;
+                for new_text in new_texts {
+                    let entry = next_layer.entry((new_text, new_remaining.clone())).or_insert_with(|| {
+                        let mut path = partial.path.clone();
+                        path.push(distinct[slot]);
+                        Partial { orderings: 0, path }
+                    });
+                    entry.orderings = entry.orderings.saturating_add(partial.orderings);
+                }
+            }
+        }
+        
+        layer = next_layer;
+    }
+    
+    // Every remaining state has used up all hints; merge them by text
+    let mut by_text: HashMap<String, Terminal> = HashMap::new();
+    for ((text, _), partial) in layer {
+        let terminal = by_text.entry(text.clone()).or_insert_with(|| Terminal {
+            text,
+            orderings: 0,
+            path: partial.path.iter().map(|&index| hints[index].name.clone()).collect(),
+        });
+        terminal.orderings = terminal.orderings.saturating_add(partial.orderings);
+    }
+    
+    let mut terminals: Vec<Terminal> = by_text.into_values().collect();
+    terminals.sort_by(|a, b| b.orderings.cmp(&a.orderings).then_with(|| a.text.cmp(&b.text)));
+    (terminals, blocked_orderings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hint::{HintOperation, OccurrenceMode};
+    
+    #[test]
+    fn independent_hints_give_a_unique_answer() {
+        let hints = [Hint::new("a", HintOperation::remove("あ")), Hint::new("i", HintOperation::remove("い")), Hint::new("u", HintOperation::remove("う"))];
+        let report = verify_uniqueness("あいうえ", &hints, "え");
+        assert!(report.unique);
+        assert_eq!(report.terminals.len(), 1);
+        assert_eq!(report.terminals[0].orderings, 6);
+        assert_eq!(report.blocked_orderings, 0);
+    }
+    
+    #[test]
+    fn order_dependent_hints_are_ambiguous() {
+        // か→き first gives くく, き→く first gives きく
+        let hints = [Hint::new("p", HintOperation::replace("か", "き")), Hint::new("q", HintOperation::replace("き", "く"))];
+        let report = verify_uniqueness("かき", &hints, "きく");
+        assert!(report.answer_reachable);
+        assert!(!report.unique);
+        let terminals: Vec<(&str, u64)> = report.terminals.iter().map(|t| (t.text.as_str(), t.orderings)).collect();
+        assert_eq!(terminals, vec![("きく", 1), ("くく", 1)]);
+        assert_eq!(report.terminals[0].path, vec!["q", "p"]);
+        
+        assert!(!verify_uniqueness("かき", &hints, "かき").answer_reachable);
+    }
+    
+    #[test]
+    fn orders_that_get_stuck_are_counted() {
+        // い→う only changes the text once あ→い has run
+        let hints = [Hint::new("p", HintOperation::replace("あ", "い")), Hint::new("q", HintOperation::replace("い", "う"))];
+        let report = verify_uniqueness("あ", &hints, "う");
+        assert!(report.unique);
+        assert_eq!(report.terminals[0].orderings, 1);
+        assert_eq!(report.blocked_orderings, 1);
+        
+        // Identical hints are interchangeable: the second one finds nothing
+        // left, and both orders are counted once as a group
+        let report = verify_uniqueness("あい", &[Hint::new("a", HintOperation::remove("あ")), Hint::new("b", HintOperation::remove("あ"))], "い");
+        assert!(report.terminals.is_empty());
+        assert!(!report.answer_reachable);
+        assert_eq!(report.blocked_orderings, 1);
+    }
+    
+    #[test]
+    fn single_occurrence_hints_branch_over_occurrences() {
+        let remove_ko = Hint { occurrences: Some(OccurrenceMode::Single), ..Hint::new("こ", HintOperation::remove("こ")) };
+        let report = verify_uniqueness("こあこ", &[remove_ko.clone(), remove_ko], "あ");
+        assert!(report.unique);
+        // Either こ first, then the other one
+        assert_eq!(report.terminals[0].orderings, 2);
+    }
+    
+    #[test]
+    fn hints_matching_differently_are_not_merged() {
+        let mut remove_any_ka = Hint::new("b", HintOperation::remove("か"));
+        remove_any_ka.ignore_voicing = true;
+        let report = verify_uniqueness("かが", &[Hint::new("a", HintOperation::remove("か")), remove_any_ka], "");
+        
+        // a then b empties the text; b first leaves nothing for a to remove
+        assert!(report.unique);
+        assert_eq!(report.terminals[0].path, vec!["a", "b"]);
+        assert_eq!(report.terminals[0].orderings, 1);
+        assert_eq!(report.blocked_orderings, 1);
+    }
+}
@@ -0,0 +1,257 @@
+// Thin wasm-bindgen layer over the native engine
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use wasm_bindgen::prelude::*;
+
+use crate::config::SearchConfig;
+use crate::constraints::HintConstraints;
+use crate::dictionary::{Dictionary, DictionaryMode};
+use crate::engine::{Partition, Pathfinder, SearchMode, SearchResult, SearchStep};
+use crate::enumerate::SolutionEnumerator;
+use crate::error::Error;
+use crate::hint::{parse_hints, OccurrenceMode};
+use crate::kana::Normalization;
+
+fn to_js_error(error: Error) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
+
+#[wasm_bindgen]
+pub struct PathfinderEngine {
+    inner: Pathfinder,
+}
+
+#[wasm_bindgen]
+impl PathfinderEngine {
+    #[wasm_bindgen(constructor)]
+    pub fn new(start: &str, target: &str, hints_json: &str, max_depth: usize) -> Result<PathfinderEngine, JsValue> {
+        Self::create(start, target, hints_json, max_depth, SearchMode::Heuristic)
+    }
+    
+    // Create an engine that also searches backward from the target and
+    // stops as soon as the two frontiers meet
+    pub fn new_bidirectional(start: &str, target: &str, hints_json: &str, max_depth: usize) -> Result<PathfinderEngine, JsValue> {
+        Self::create(start, target, hints_json, max_depth, SearchMode::Bidirectional)
+    }
+    
+    // Create an engine that runs A* on a provable lower bound, so the first
+    // path it returns has the lowest possible total hint cost
+    pub fn new_optimal(start: &str, target: &str, hints_json: &str, max_depth: usize) -> Result<PathfinderEngine, JsValue> {
+        Self::create(start, target, hints_json, max_depth, SearchMode::Optimal)
+    }
+    
+    // Create an engine with explicit kana normalization options, given as an
+    // object like `{ katakana_to_hiragana: true, small_kana_to_full_size: true }`;
+    // missing options keep their defaults
+    pub fn with_normalization(start: &str, target: &str, hints_json: &str, max_depth: usize, mode: &str, normalization: JsValue) -> Result<PathfinderEngine, JsValue> {
+        #[cfg(feature = "console_error_panic_hook")]
+        console_error_panic_hook::set_once();
+        
+        let mode: SearchMode = mode.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        let normalization: Normalization = serde_wasm_bindgen::from_value(normalization)
+            .map_err(|e| JsValue::from_str(&format!("Invalid normalization options: {}", e)))?;
+        let hints = parse_hints(hints_json).map_err(to_js_error)?;
+        let inner = Pathfinder::with_normalization(start, target, hints, max_depth, mode, normalization)
+            .map_err(to_js_error)?;
+        Ok(PathfinderEngine { inner })
+    }
+    
+    // Create an engine from a SearchConfig object, e.g.
+    // `{ mode: "optimal", max_depth: 8, cost_weight: 0.2 }`; missing fields
+    // keep their defaults
+    pub fn with_config(start: &str, target: &str, hints_json: &str, config: JsValue) -> Result<PathfinderEngine, JsValue> {
+        #[cfg(feature = "console_error_panic_hook")]
+        console_error_panic_hook::set_once();
+        
+        let config: SearchConfig = serde_wasm_bindgen::from_value(config)
+            .map_err(|e| JsValue::from_str(&format!("Invalid search config: {}", e)))?;
+        let hints = parse_hints(hints_json).map_err(to_js_error)?;
+        let inner = Pathfinder::with_config(start, target, hints, config).map_err(to_js_error)?;
+        Ok(PathfinderEngine { inner })
+    }
+    
+    // Make every hint without its own setting rewrite one occurrence per move
+    // ("single") or all of them at once ("all", the default)
+    pub fn set_occurrence_mode(&mut self, mode: &str) -> Result<(), JsValue> {
+        let mode: OccurrenceMode = mode.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        self.inner.set_occurrence_mode(mode);
+        Ok(())
+    }
+    
+    // Limit, require or ban hints by name; takes a HintConstraints object
+    // and must be called before the search starts
+    pub fn set_constraints(&mut self, constraints: JsValue) -> Result<(), JsValue> {
+        let constraints: HintConstraints = serde_wasm_bindgen::from_value(constraints)
+            .map_err(|e| JsValue::from_str(&format!("Invalid constraints: {}", e)))?;
+        self.inner.set_constraints(constraints).map_err(to_js_error)
+    }
+    
+    // Check intermediate texts against a plain-text word list, one word per
+    // line; `mode` is "require" or "prefer"
+    pub fn set_word_list(&mut self, words: &str, mode: &str) -> Result<(), JsValue> {
+        let mode: DictionaryMode = mode.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        self.inner.set_dictionary(Dictionary::from_word_list(words), mode);
+        Ok(())
+    }
+    
+    // Same as `set_word_list`, with the words in a prebuilt FST set
+    pub fn set_fst_dictionary(&mut self, bytes: Vec<u8>, mode: &str) -> Result<(), JsValue> {
+        let mode: DictionaryMode = mode.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        let dictionary = Dictionary::from_fst(bytes).map_err(to_js_error)?;
+        self.inner.set_dictionary(dictionary, mode);
+        Ok(())
+    }
+    
+    // Run search for a specified number of iterations
+    pub fn run_iterations(&mut self, iterations: usize) -> JsValue {
+        match self.inner.step(iterations) {
+            SearchStep::Found(result) => serde_wasm_bindgen::to_value(&result).unwrap(),
+            SearchStep::Progress(progress) => serde_wasm_bindgen::to_value(&progress).unwrap(),
+        }
+    }
+    
+    // Check if search is complete
+    pub fn is_complete(&self) -> bool {
+        self.inner.is_complete()
+    }
+    
+    // Save the search so it can be resumed with `from_state`
+    pub fn export_state(&self) -> Vec<u8> {
+        self.inner.export_state()
+    }
+    
+    // Restore an engine saved with `export_state`
+    pub fn from_state(bytes: &[u8]) -> Result<PathfinderEngine, JsValue> {
+        let inner = Pathfinder::from_state(bytes).map_err(to_js_error)?;
+        Ok(PathfinderEngine { inner })
+    }
+    
+    // Get final result
+    pub fn get_result(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.inner.result()).unwrap()
+    }
+    
+    // Hits, misses and sizes of the engine's caches
+    pub fn get_cache_stats(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.inner.cache_stats()).unwrap()
+    }
+    
+    // Make this engine partition `index` of `count` engines, one per worker,
+    // that split a search; must be called before the search starts
+    pub fn set_partition(&mut self, index: u32, count: u32) -> Result<(), JsValue> {
+        let partition = Partition::new(index, count).map_err(to_js_error)?;
+        self.inner.set_partition(partition).map_err(to_js_error)
+    }
+    
+    // States reached for other partitions, as `[{ partition, data }]` where
+    // `data` is a Uint8Array for that partition's `import_states`
+    pub fn take_outgoing(&mut self) -> js_sys::Array {
+        self.inner.take_outgoing().into_iter()
+            .map(|batch| {
+                let entry = js_sys::Object::new();
+                js_sys::Reflect::set(&entry, &"partition".into(), &batch.partition.into()).unwrap();
+                js_sys::Reflect::set(&entry, &"data".into(), &js_sys::Uint8Array::from(&batch.data[..])).unwrap();
+                entry
+            })
+            .collect()
+    }
+    
+    // Queue a batch of states another partition sent; returns how many were new
+    pub fn import_states(&mut self, data: &[u8]) -> Result<usize, JsValue> {
+        self.inner.import_states(data).map_err(to_js_error)
+    }
+    
+    // Lowest score left in the queue, for telling when an optimal partitioned
+    // search has found the cheapest path
+    pub fn frontier_bound(&self) -> Option<f64> {
+        self.inner.frontier_bound()
+    }
+}
+
+impl PathfinderEngine {
+    fn create(start: &str, target: &str, hints_json: &str, max_depth: usize, mode: SearchMode) -> Result<PathfinderEngine, JsValue> {
+        #[cfg(feature = "console_error_panic_hook")]
+        console_error_panic_hook::set_once();
+        
+        let inner = Pathfinder::from_json(start, target, hints_json, max_depth, mode)
+            .map_err(to_js_error)?;
+        Ok(PathfinderEngine { inner })
+    }
+}
+
+#[wasm_bindgen(js_name = SolutionEnumerator)]
+pub struct WasmSolutionEnumerator {
+    inner: SolutionEnumerator,
+}
+
+#[wasm_bindgen(js_class = SolutionEnumerator)]
+impl WasmSolutionEnumerator {
+    // `max_solutions` caps how many solutions are produced; 0 means no limit
+    #[wasm_bindgen(constructor)]
+    pub fn new(start: &str, target: &str, hints_json: &str, max_depth: usize, max_solutions: usize) -> Result<WasmSolutionEnumerator, JsValue> {
+        let inner = SolutionEnumerator::from_json(start, target, hints_json, max_depth, max_solutions)
+            .map_err(to_js_error)?;
+        Ok(WasmSolutionEnumerator { inner })
+    }
+    
+    // Expand up to `iterations` states and return the solutions completed meanwhile
+    pub fn run_iterations(&mut self, iterations: usize) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.inner.enumerate(iterations)).unwrap()
+    }
+    
+    // Check if enumeration is complete
+    pub fn is_complete(&self) -> bool {
+        self.inner.is_complete()
+    }
+}
+
+// Build a question from an answer and an ordered hint list; the same seed
+// always gives the same question
+#[wasm_bindgen]
+pub fn encode(answer: &str, hints_json: &str, seed: u32) -> Result<String, JsValue> {
+    let hints = parse_hints(hints_json).map_err(to_js_error)?;
+    crate::encode::encode(answer, &hints, &mut SmallRng::seed_from_u64(seed as u64)).map_err(to_js_error)
+}
+
+// Try every application order of the hints on the question and report the
+// strings they can produce, so ambiguous puzzles can be flagged
+#[wasm_bindgen]
+pub fn verify_uniqueness(question: &str, hints_json: &str, answer: &str) -> Result<JsValue, JsValue> {
+    let hints = parse_hints(hints_json).map_err(to_js_error)?;
+    let report = crate::verify::verify_uniqueness(question, &hints, answer);
+    Ok(serde_wasm_bindgen::to_value(&report).unwrap())
+}
+
+// Score how hard the question is to solve with the given hint pool
+#[wasm_bindgen]
+pub fn analyze_difficulty(question: &str, answer: &str, puzzle_hints_json: &str, hint_pool_json: &str, max_depth: usize) -> Result<JsValue, JsValue> {
+    let puzzle_hints = parse_hints(puzzle_hints_json).map_err(to_js_error)?;
+    let hint_pool = parse_hints(hint_pool_json).map_err(to_js_error)?;
+    let report = crate::difficulty::analyze_difficulty(question, answer, &puzzle_hints, &hint_pool, max_depth)
+        .map_err(to_js_error)?;
+    Ok(serde_wasm_bindgen::to_value(&report).unwrap())
+}
+
+// Rank the answers a question can have when every hint is applied once.
+// `words` is an optional plain-text word list used to favour real words.
+#[wasm_bindgen]
+pub fn solve(question: &str, hints_json: &str, words: Option<String>) -> Result<JsValue, JsValue> {
+    let hints = parse_hints(hints_json).map_err(to_js_error)?;
+    let dictionary = words.as_deref().map(Dictionary::from_word_list);
+    let result = crate::solve::solve(question, &hints, dictionary.as_ref());
+    Ok(serde_wasm_bindgen::to_value(&result).unwrap())
+}
+
+// Combine the final results of every partition of a search into one
+#[wasm_bindgen]
+pub fn merge_results(results: JsValue) -> Result<JsValue, JsValue> {
+    let results: Vec<SearchResult> = serde_wasm_bindgen::from_value(results)
+        .map_err(|e| JsValue::from_str(&format!("Invalid search results: {}", e)))?;
+    Ok(serde_wasm_bindgen::to_value(&crate::engine::merge_results(results)).unwrap())
+}
+
+#[wasm_bindgen]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}